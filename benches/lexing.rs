@@ -0,0 +1,120 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use lexer::modules::{
+    analyser::analyze_chars,
+    interner::{Interner, Symbol},
+    language::LX,
+    source_map::SourceMap,
+    tokenizer::tokenize,
+    tokens::Token,
+};
+
+/// Counts the allocations and allocated bytes of the whole process.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// `lines` lines that reference a few dozen names over and over.
+fn source(lines: usize) -> String {
+    (0..lines)
+        .map(|i| {
+            format!(
+                "let value{} = total + value{} * count;\n",
+                i % 40,
+                (i + 1) % 40
+            )
+        })
+        .collect()
+}
+
+/// Allocations and bytes allocated while running `f`.
+fn allocated<T>(f: impl FnOnce() -> T) -> (usize, usize) {
+    let (allocations, bytes) = (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        BYTES.load(Ordering::Relaxed),
+    );
+    black_box(f());
+    (
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+fn names(source: &str) -> Vec<&str> {
+    source
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.starts_with(|c: char| c.is_alphabetic()) && *w != "let")
+        .collect()
+}
+
+fn lexing(c: &mut Criterion) {
+    let source = source(10_000);
+    let mut sources = SourceMap::new();
+    let file = sources.add("large.lx", source.clone());
+    let chars = analyze_chars(&source);
+
+    // what one identifier costs now, and what a `String` per identifier cost before
+    let tokens = tokenize(&chars, file).unwrap();
+    let identifiers = tokens
+        .iter()
+        .filter(|t| matches!(t.token, Token::Identifier(_)))
+        .count();
+    let (allocations, bytes) = allocated(|| tokenize(&chars, file).unwrap());
+    let words = names(&source);
+    let (string_allocations, string_bytes) =
+        allocated(|| words.iter().map(|w| w.to_string()).collect::<Vec<String>>());
+    println!(
+        "{identifiers} identifiers, {} distinct: lexing allocates {allocations} times, \
+         {bytes} bytes; a String for each would take {string_allocations} allocations, \
+         {string_bytes} bytes",
+        // every stream starts with the symbols of the language
+        tokens.interner().len() - LX.interner().len(),
+    );
+
+    let mut group = c.benchmark_group("tokenize");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("10k lines", |b| {
+        b.iter(|| tokenize(black_box(&chars), file).unwrap())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("names");
+    group.throughput(Throughput::Elements(words.len() as u64));
+    group.bench_function("String per identifier", |b| {
+        b.iter(|| {
+            let strings: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+            // comparing two names compares their text
+            strings.windows(2).filter(|w| w[0] == w[1]).count()
+        })
+    });
+    group.bench_function("interned Symbol", |b| {
+        b.iter(|| {
+            let mut interner = Interner::new();
+            let symbols: Vec<Symbol> = words.iter().map(|w| interner.intern(w)).collect();
+            // comparing two names compares two numbers
+            symbols.windows(2).filter(|w| w[0] == w[1]).count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, lexing);
+criterion_main!(benches);
@@ -0,0 +1,13 @@
+mod doc;
+mod explain;
+mod fix;
+mod fmt;
+mod grammar;
+mod lex;
+
+pub use doc::doc;
+pub use explain::explain;
+pub use fix::fix;
+pub use fmt::fmt;
+pub use grammar::grammar;
+pub use lex::lex;
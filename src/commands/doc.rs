@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use lexer::error_handling::{Error, Result};
+use lexer::modules::{
+    analyser::analyze_chars,
+    args_handler::Args,
+    docs::collect_docs,
+    source_map::SourceMap,
+    tokenizer::{TokenizeOptions, tokenize_with},
+};
+
+pub fn doc(args: &Args, sources: &mut SourceMap) -> Result<ExitCode> {
+    let html = args.get_arg("format").is_ok_and(|format| format == "html");
+    let output = args.get_arg("output").ok();
+    let options = TokenizeOptions::new().with_language(args.get_language()?);
+
+    for path in args.get_many("files") {
+        let file = sources.load(&path)?;
+        let source = sources.get(file).text();
+        let tokens = tokenize_with(&analyze_chars(source), file, &options)?;
+        let docs = collect_docs(source, &tokens);
+
+        let title = Path::new(&path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+        let (page, extension) = if html {
+            (docs.to_html(&title), "html")
+        } else {
+            (docs.to_markdown(&title), "md")
+        };
+
+        match &output {
+            Some(dir) => {
+                fs::create_dir_all(dir).map_err(|e| Error::io(e, dir))?;
+                let target = Path::new(dir).join(format!("{title}.{extension}"));
+                let target = target.display().to_string();
+                fs::write(&target, page).map_err(|e| Error::io(e, &target))?;
+            }
+            None => print!("{page}"),
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
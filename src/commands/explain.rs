@@ -0,0 +1,20 @@
+use std::process::ExitCode;
+
+use lexer::error_handling::ErrorCode;
+
+pub fn explain(code: &str) -> ExitCode {
+    match ErrorCode::from_code(code) {
+        Some(code) => {
+            println!("{}: {}\n", code, code.title());
+            print!("{}", code.explanation());
+            ExitCode::SUCCESS
+        }
+        None => {
+            eprintln!("'{code}' is not a known error code, known codes are:");
+            for code in ErrorCode::ALL {
+                eprintln!("  {}  {}", code, code.title());
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
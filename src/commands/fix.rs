@@ -0,0 +1,30 @@
+use std::fs;
+use std::process::ExitCode;
+
+use lexer::error_handling::{Diagnostics, Error, Result};
+use lexer::modules::{
+    args_handler::Args, fixer::fix_source_with, source_map::SourceMap, tokenizer::TokenizeOptions,
+};
+
+pub fn fix(
+    args: &Args,
+    sources: &mut SourceMap,
+    diagnostics: &mut Diagnostics,
+) -> Result<ExitCode> {
+    let options = TokenizeOptions::new().with_language(args.get_language()?);
+    for path in args.get_many("files") {
+        let file = sources.load(&path)?;
+        let text = sources.get(file).text().to_string();
+
+        let fixed = fix_source_with(sources, &path, text, &options, diagnostics);
+        if fixed.applied > 0 {
+            fs::write(&path, sources.get(fixed.file).text()).map_err(|e| Error::io(e, &path))?;
+            eprintln!("fixed {} issue(s) in {path}", fixed.applied);
+        }
+        fixed
+            .remaining
+            .into_iter()
+            .for_each(|d| diagnostics.push(d));
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::process::ExitCode;
+
+use lexer::error_handling::{Diagnostics, Error, Result};
+use lexer::modules::{
+    args_handler::Args,
+    formatter::{format_source_with, render_diff},
+    source_map::SourceMap,
+    tokenizer::TokenizeOptions,
+};
+
+pub fn fmt(
+    args: &Args,
+    sources: &mut SourceMap,
+    diagnostics: &mut Diagnostics,
+) -> Result<ExitCode> {
+    let check = args.get_flag("check");
+    let mut failed = false;
+    let options = TokenizeOptions::new().with_language(args.get_language()?);
+
+    // a file that cannot be read or lexed is reported, the others are still formatted
+    for path in args.get_many("files") {
+        match fmt_file(sources, &path, &options, check) {
+            Ok(unformatted) => failed |= unformatted,
+            Err(err) => {
+                diagnostics.push(err);
+                failed = true;
+            }
+        }
+    }
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Formats the file at `path` in place, or with `check` prints how it differs; true if
+/// `check` found it unformatted.
+fn fmt_file(
+    sources: &mut SourceMap,
+    path: &str,
+    options: &TokenizeOptions,
+    check: bool,
+) -> Result<bool> {
+    let file = sources.load(path)?;
+    let formatted = format_source_with(sources, file, options)?;
+    let source = sources.get(file).text();
+    if formatted == source {
+        return Ok(false);
+    }
+    if check {
+        print!("{}", render_diff(path, source, &formatted));
+        return Ok(true);
+    }
+    // the source map drops a byte order mark, the file keeps it
+    let bom = if sources.get(file).has_bom() {
+        "\u{feff}"
+    } else {
+        ""
+    };
+    fs::write(path, format!("{bom}{formatted}")).map_err(|e| Error::io(e, path))?;
+    Ok(false)
+}
@@ -0,0 +1,25 @@
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use lexer::error_handling::{Error, Result};
+use lexer::modules::{args_handler::Args, grammar::GrammarFormat};
+
+pub fn grammar(args: &Args) -> Result<ExitCode> {
+    let spec = args.get_language()?;
+    let name = args.get_arg("name")?;
+    let formats = match args.get_arg("format").ok().as_deref() {
+        Some("all") | None => GrammarFormat::ALL.to_vec(),
+        Some(format) => GrammarFormat::from_name(format).into_iter().collect(),
+    };
+
+    let dir = args.get_arg("output")?;
+    fs::create_dir_all(&dir).map_err(|e| Error::io(e, &dir))?;
+    for format in formats {
+        let target = Path::new(&dir).join(format.file_name(&name));
+        let target = target.display().to_string();
+        fs::write(&target, format.generate(&spec, &name)).map_err(|e| Error::io(e, &target))?;
+        eprintln!("wrote {target}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,39 @@
+use std::process::ExitCode;
+
+use lexer::error_handling::{Diagnostics, Result};
+use lexer::modules::{
+    analyser::AnalyzeOptions,
+    args_handler::Args,
+    batch::{BatchOptions, lex_files_with, source_files},
+    source_map::SourceMap,
+    tokenizer::TokenizeOptions,
+};
+
+pub fn lex(
+    args: &Args,
+    sources: &mut SourceMap,
+    diagnostics: &mut Diagnostics,
+) -> Result<ExitCode> {
+    let extension = args.get_arg("extension")?;
+    let paths = source_files(&args.get_many("paths"), &extension)?;
+    let options = BatchOptions::new()
+        .with_jobs(args.get_jobs())
+        .with_analyze_options(AnalyzeOptions::new().with_tab_width(args.get_tab_width()))
+        .with_tokenize_options(TokenizeOptions::new().with_language(args.get_language()?));
+
+    let batch = lex_files_with(sources, &paths, &options)?;
+    let summary = batch.summary;
+    for file in batch.files {
+        if let Err(err) = file.result {
+            diagnostics.push(err);
+        }
+    }
+    println!(
+        "{} files, {} tokens, {} errors, {:.1} MB/s",
+        summary.files,
+        summary.tokens,
+        summary.errors,
+        summary.bytes_per_second() / 1_000_000.0
+    );
+    Ok(ExitCode::SUCCESS)
+}
@@ -1,3 +1,15 @@
+mod codes;
+mod diagnostics;
+mod emit;
 mod error;
 mod error_path;
+mod label;
+mod renderer;
+mod suggestion;
+pub use codes::ErrorCode;
+pub use diagnostics::{Diagnostics, Level, Severity};
+pub use emit::{ErrorFormat, to_json, to_sarif};
 pub use error::{Error, ErrorType, Result};
+pub use label::{Label, Span};
+pub use renderer::Renderer;
+pub use suggestion::{Applicability, Suggestion};
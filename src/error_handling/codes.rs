@@ -0,0 +1,400 @@
+use std::fmt::Display;
+
+use super::diagnostics::Level;
+
+/// Stable identifier of a distinct diagnostic, e.g. `L0001`.
+///
+/// Codes never change meaning once released; retired codes are kept in the catalogue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    UnclosedString,
+    UnclosedChar,
+    MultiCharLiteral,
+    IdentifierStartsWithDigit,
+    IntegerOverflow,
+    UnexpectedCharacter,
+    UnclosedInterpolation,
+    Io,
+    MissingArgument,
+    InvalidLanguageSpec,
+    UnexpectedToken,
+    UnreachableCode,
+    KeywordLikeIdentifier,
+    MixedIndentation,
+    TrailingWhitespace,
+    MisspelledKeyword,
+    ConfusableIdentifier,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 17] = [
+        ErrorCode::UnclosedString,
+        ErrorCode::UnclosedChar,
+        ErrorCode::MultiCharLiteral,
+        ErrorCode::IdentifierStartsWithDigit,
+        ErrorCode::IntegerOverflow,
+        ErrorCode::UnexpectedCharacter,
+        ErrorCode::UnclosedInterpolation,
+        ErrorCode::Io,
+        ErrorCode::MissingArgument,
+        ErrorCode::InvalidLanguageSpec,
+        ErrorCode::UnexpectedToken,
+        ErrorCode::UnreachableCode,
+        ErrorCode::KeywordLikeIdentifier,
+        ErrorCode::MixedIndentation,
+        ErrorCode::TrailingWhitespace,
+        ErrorCode::MisspelledKeyword,
+        ErrorCode::ConfusableIdentifier,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UnclosedString => "L0001",
+            Self::UnclosedChar => "L0002",
+            Self::MultiCharLiteral => "L0003",
+            Self::IdentifierStartsWithDigit => "L0004",
+            Self::IntegerOverflow => "L0005",
+            Self::UnexpectedCharacter => "L0006",
+            Self::UnclosedInterpolation => "L0007",
+            Self::Io => "E0001",
+            Self::MissingArgument => "E0002",
+            Self::InvalidLanguageSpec => "E0003",
+            Self::UnexpectedToken => "P0001",
+            Self::UnreachableCode => "W0001",
+            Self::KeywordLikeIdentifier => "W0002",
+            Self::MixedIndentation => "W0003",
+            Self::TrailingWhitespace => "W0004",
+            Self::MisspelledKeyword => "W0005",
+            Self::ConfusableIdentifier => "W0006",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<ErrorCode> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(code))
+    }
+
+    /// Lints are the warnings that `-W`, `-A` and `--deny-warnings` act on.
+    pub fn is_lint(&self) -> bool {
+        self.as_str().starts_with('W')
+    }
+
+    /// How a code is reported when no command-line flag says otherwise.
+    pub fn default_level(&self) -> Level {
+        match self {
+            Self::TrailingWhitespace => Level::Allow,
+            code if code.is_lint() => Level::Warn,
+            _ => Level::Deny,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::UnclosedString => "unclosed string literal",
+            Self::UnclosedChar => "unclosed char literal",
+            Self::MultiCharLiteral => "char literal with more than one character",
+            Self::IdentifierStartsWithDigit => "identifier starts with a digit",
+            Self::IntegerOverflow => "integer literal out of range",
+            Self::UnexpectedCharacter => "unexpected character in a token",
+            Self::UnclosedInterpolation => "unclosed interpolation in a string",
+            Self::Io => "source could not be read",
+            Self::MissingArgument => "missing command-line argument",
+            Self::InvalidLanguageSpec => "invalid language definition",
+            Self::UnexpectedToken => "unexpected token",
+            Self::UnreachableCode => "unreachable code",
+            Self::KeywordLikeIdentifier => "identifier looks like a keyword",
+            Self::MixedIndentation => "mixed tabs and spaces in indentation",
+            Self::TrailingWhitespace => "trailing whitespace",
+            Self::MisspelledKeyword => "identifier looks like a misspelled keyword",
+            Self::ConfusableIdentifier => "identifier mixes scripts or has look-alike characters",
+        }
+    }
+
+    /// The long explanation shown by `enoc --explain`.
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            Self::UnclosedString => {
+                r##"A string literal was opened but not closed before the end of its line, or for
+a multi-line string, before the end of the file.
+
+Erroneous examples:
+
+    let s: string = "Hello, world;
+    let p: string = r#"C:\path";
+
+Ordinary `"..."` and raw `r"..."`/`r#"..."#` strings end on the line they
+start. Close the string where it is meant to end, with as many `#` as the raw
+string was opened with:
+
+    let s: string = "Hello, world";
+    let p: string = r#"C:\path"#;
+
+Text over several lines goes into a triple-quoted string, whose common
+indentation is removed:
+
+    let text: string = """
+        first line
+        second line
+        """;
+"##
+            }
+            Self::UnclosedChar => {
+                r#"A char literal was opened with `'` but no closing `'` follows on its line.
+
+Erroneous examples:
+
+    let c: char = 'a;
+    let d: char = '
+
+A char literal is exactly one character between single quotes:
+
+    let c: char = 'a';
+"#
+            }
+            Self::MultiCharLiteral => {
+                r#"A char literal must hold exactly one character between its quotes.
+
+Erroneous example:
+
+    let c: char = 'ab';
+
+Either keep a single character and close the literal, or use a string for
+longer text:
+
+    let c: char = 'a';
+    let s: string = "ab";
+"#
+            }
+            Self::IdentifierStartsWithDigit => {
+                r#"An identifier started with a digit.
+
+Erroneous example:
+
+    let 123var: i32 = 5;
+
+Identifiers must start with a letter or `_` and may contain letters, digits
+and `_` after that:
+
+    let var123: i32 = 5;
+    let _123var: i32 = 5;
+"#
+            }
+            Self::IntegerOverflow => {
+                r#"An integer literal does not fit into `i32`.
+
+Erroneous example:
+
+    let big: i32 = 3000000000;
+
+Integer literals are stored as `i32`, so they must lie between -2147483648
+and 2147483647.
+"#
+            }
+            Self::UnexpectedCharacter => {
+                r#"A word contained a character that is neither part of an identifier nor an
+operator the lexer knows. Identifiers follow Unicode UAX #31: they start with
+a letter (XID_Start) or `_` and continue with letters, digits, combining marks
+and `_` (XID_Continue), so punctuation and invisible characters such as the
+zero width space U+200B are rejected.
+
+Erroneous examples:
+
+    let a$b: i32 = 1;
+    if x & y { }
+
+Identifiers may only contain letters, digits and `_`. Use `and` and `or` for
+the logical operators:
+
+    let a_b: i32 = 1;
+    if x and y { }
+"#
+            }
+            Self::UnclosedInterpolation => {
+                r#"A `{` inside a string starts an interpolated expression, but the line ended
+before the `}` that closes it.
+
+Erroneous example:
+
+    let msg: string = "You have {count + 1 items";
+
+Close the expression, or double the brace to get a literal `{`:
+
+    let msg: string = "You have {count + 1} items";
+    let set: string = "{{1, 2}}"; // the text {1, 2}
+"#
+            }
+            Self::Io => {
+                r#"A source file could not be opened or read.
+
+Check that the path passed to `--source` exists, is readable, and that the
+file is valid UTF-8.
+"#
+            }
+            Self::MissingArgument => {
+                r#"A required command-line argument was not given.
+
+Pass the file to lex with `--source <path>`, or pick one of the bundled
+examples with `--example <name>`:
+
+    enoc --source main.lx
+    enoc --example correct_syntax.lx
+"#
+            }
+            Self::InvalidLanguageSpec => {
+                r##"The language definition passed with `--language` is not valid TOML or
+does not describe a language.
+
+Every table is optional and replaces the built-in `.lx` one as a whole.
+Keywords and operators map their spelling to a token kind, written as the
+name of a `Token` variant; `IntType`, `FloatType` and `Symbol` take the
+spelling as their text unless it is given in parentheses. The text of a
+type has to be an `.lx` type such as `i64` or `f32`:
+
+    [keywords]
+    fn = "Op"
+    int = "IntType(i64)"
+    and = "Symbol(&&)"
+
+    [operators]
+    ";" = "Semicolon"
+    "=>" = "Arrow"
+
+    [comments]
+    line = "#"
+
+    [strings]
+    quote = "'"
+    char_quote = "`"
+
+    [identifiers]
+    unicode = false
+    start = "_$"
+"##
+            }
+            Self::UnexpectedToken => {
+                r#"A parser expected one token and found another, or the end of the file.
+
+Erroneous example:
+
+    let x: i32 = 1
+    let y: i32 = 2;
+
+The first statement is missing its `;`, so the parser finds the `let` of the
+second one in its place:
+
+    Unexpected token! -> expected `;`, found `let`
+
+Add the missing token:
+
+    let x: i32 = 1;
+    let y: i32 = 2;
+
+Without the second statement the parser runs out of tokens instead; the end of
+the file is reported just past the last character:
+
+    Unexpected token! -> expected `;`, found end of file
+"#
+            }
+            Self::UnreachableCode => {
+                r#"A statement follows `break`, `continue` or `success`, or an `if`/`else`
+whose branches all end in one of them, so it can never run.
+
+Erroneous example:
+
+    for i : 0->100 {
+        if i < 10 {
+            break;
+        } else {
+            continue;
+        }
+        print(i); // never reached
+    }
+
+Remove the statement or move it before the jump. This lint is on by default;
+silence it with `-A W0001`.
+"#
+            }
+            Self::KeywordLikeIdentifier => {
+                r#"An identifier is spelled like a keyword of another language, or like a
+keyword of this one with different spelling, so it is probably a mistake.
+
+Erroneous example:
+
+    let count: int = 1;
+    op main() void { return; }
+
+`int`, `void` and `return` are plain identifiers here. The keywords are
+`i32`, `nret` and `success`:
+
+    let count: i32 = 1;
+    op main() nret { success; }
+
+This lint is on by default; silence it with `-A W0002`.
+"#
+            }
+            Self::MixedIndentation => {
+                r#"A line is indented with both tabs and spaces, or the file indents some lines
+with tabs and others with spaces.
+
+Columns of mixed indentation depend on the tab width of whoever reads the
+file. Pick one style for the whole file; `enoc fmt` re-indents with spaces.
+This lint is on by default; silence it with `-A W0003`.
+"#
+            }
+            Self::TrailingWhitespace => {
+                r#"A line ends with spaces or tabs.
+
+Trailing whitespace is invisible in most editors and causes noisy diffs.
+`enoc fmt` removes it. This lint is off by default; enable it with `-W W0004`.
+"#
+            }
+            Self::MisspelledKeyword => {
+                r#"An identifier is one or two typing mistakes away from a keyword.
+
+Erroneous example:
+
+    whlie i < 10 {
+        i = i + 1;
+    }
+    retrun i;
+
+A misspelled keyword is lexed as a plain identifier, so the mistake only shows
+up later, if at all. The keywords are `while` and `success`:
+
+    while i < 10 {
+        i = i + 1;
+    }
+    success i;
+
+This lint is on by default; silence it with `-A W0005` when the name is
+intended.
+"#
+            }
+            Self::ConfusableIdentifier => {
+                r#"An identifier mixes letters of different scripts, or is made of letters that
+look like Latin ones but are not.
+
+Erroneous example:
+
+    let dаta: i32 = 1; // the `а` is Cyrillic U+0430
+    print(data);       // a different name
+
+Both names look the same on screen but are different identifiers. Retype the
+name with the letters you meant:
+
+    let data: i32 = 1;
+
+Identifiers written entirely in one script, like `değişken` or `переменная`,
+are fine. This lint is on by default; silence it with `-A W0006`.
+"#
+            }
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::codes::ErrorCode;
+use super::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Help,
+    Note,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Help => "help",
+            Self::Note => "note",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// What happens to a lint when it fires: dropped, reported, or reported and failing the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// Collects every diagnostic of a run, applying the `-W`/`-A`/`--deny-warnings` levels.
+#[derive(Default)]
+pub struct Diagnostics {
+    emitted: Vec<Error>,
+    levels: HashMap<ErrorCode, Level>,
+    deny_warnings: bool,
+}
+
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Diagnostics::default()
+    }
+    pub fn with_level(mut self, code: ErrorCode, level: Level) -> Self {
+        self.levels.insert(code, level);
+        self
+    }
+    pub fn with_deny_warnings(mut self, deny: bool) -> Self {
+        self.deny_warnings = deny;
+        self
+    }
+
+    /// An empty sink with the same levels, for diagnostics that are looked at before they
+    /// are reported.
+    pub fn fresh(&self) -> Diagnostics {
+        Diagnostics {
+            emitted: Vec::new(),
+            levels: self.levels.clone(),
+            deny_warnings: self.deny_warnings,
+        }
+    }
+
+    /// The level `code` is reported at; only warnings can be allowed or denied.
+    pub fn level_of(&self, code: ErrorCode) -> Level {
+        match self.levels.get(&code) {
+            Some(level) => *level,
+            None => code.default_level(),
+        }
+    }
+
+    pub fn push(&mut self, diag: Error) {
+        if diag.severity() == Severity::Warning
+            && let Some(code) = diag.code()
+        {
+            let level = match self.level_of(code) {
+                Level::Warn if self.deny_warnings => Level::Deny,
+                level => level,
+            };
+            match level {
+                Level::Allow => return,
+                Level::Warn => {}
+                Level::Deny => {
+                    let diag = diag
+                        .with_severity(Severity::Error)
+                        .with_note(&format!("`{code}` is denied for this run"));
+                    self.emitted.push(diag);
+                    return;
+                }
+            }
+        }
+        self.emitted.push(diag);
+    }
+
+    pub fn diagnostics(&self) -> &[Error] {
+        &self.emitted
+    }
+    pub fn into_diagnostics(self) -> Vec<Error> {
+        self.emitted
+    }
+    /// Takes out everything collected so far, keeping the configured levels.
+    pub fn take(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.emitted)
+    }
+    pub fn count(&self, severity: Severity) -> usize {
+        self.emitted
+            .iter()
+            .filter(|d| d.severity() == severity)
+            .count()
+    }
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+}
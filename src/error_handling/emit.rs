@@ -0,0 +1,305 @@
+use std::error::Error as StdError;
+
+use serde_json::{Value, json};
+
+use super::codes::ErrorCode;
+use super::diagnostics::{Level, Severity};
+use super::error::Error;
+use super::label::{Label, Span};
+use super::renderer::Renderer;
+use super::suggestion::{Applicability, Suggestion};
+use crate::modules::position::PositionEncoding;
+use crate::modules::source_map::{SourceFile, SourceMap};
+
+/// How diagnostics are written out, picked with `--error-format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+    Sarif,
+}
+
+impl ErrorFormat {
+    pub fn from_name(name: &str) -> Option<ErrorFormat> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            "sarif" => Some(ErrorFormat::Sarif),
+            _ => None,
+        }
+    }
+
+    /// Writes all `errors` in this format: human diagnostics, JSON lines or one SARIF log.
+    ///
+    /// Columns are counted in `encoding`; the human format takes it from `renderer`.
+    pub fn emit(
+        &self,
+        errors: &[Error],
+        renderer: &Renderer,
+        sources: &SourceMap,
+        encoding: PositionEncoding,
+    ) -> String {
+        match self {
+            ErrorFormat::Human => errors
+                .iter()
+                .map(|err| renderer.render(err, sources))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ErrorFormat::Json => errors
+                .iter()
+                .map(|err| format!("{}\n", to_json(err, sources, encoding)))
+                .collect(),
+            ErrorFormat::Sarif => format!("{:#}\n", to_sarif(errors, sources, encoding)),
+        }
+    }
+}
+
+/// One diagnostic as a flat JSON object, its columns counted in `encoding`.
+pub fn to_json(err: &Error, sources: &SourceMap, encoding: PositionEncoding) -> Value {
+    let columns = Columns::of(err, sources, encoding);
+    let (column, length) = columns.position(err);
+    json!({
+        "code": err.code().map(|c| c.as_str()),
+        "type": err.err_type().to_string(),
+        "severity": err.severity().to_string(),
+        "message": err.message(),
+        "file": file_name(err, sources),
+        "line": err.line(),
+        "column": column,
+        "length": length,
+        "position_encoding": encoding.name(),
+        "labels": err
+            .labels()
+            .iter()
+            .map(|label| label_json(label, &columns))
+            .collect::<Vec<_>>(),
+        "notes": err.notes(),
+        "help": err.helps(),
+        "suggestions": err
+            .suggestions()
+            .iter()
+            .map(|suggestion| suggestion_json(suggestion, &columns))
+            .collect::<Vec<_>>(),
+        "causes": causes(err),
+    })
+}
+
+fn file_name<'a>(err: &'a Error, sources: &'a SourceMap) -> Option<&'a str> {
+    err.file().map(|id| sources.get(id).name()).or(err.path())
+}
+
+/// Messages of the `source()` chain below `err`, outermost first.
+fn causes(err: &Error) -> Vec<String> {
+    std::iter::successors(err.source(), |cause| (*cause).source())
+        .map(|cause| cause.to_string())
+        .collect()
+}
+
+/// All diagnostics as a single SARIF 2.1.0 log with one run.
+///
+/// SARIF only knows code point and UTF-16 columns, so UTF-8 falls back to code points.
+pub fn to_sarif(errors: &[Error], sources: &SourceMap, encoding: PositionEncoding) -> Value {
+    let encoding = match encoding {
+        PositionEncoding::Utf16 => PositionEncoding::Utf16,
+        _ => PositionEncoding::Utf32,
+    };
+    let rules: Vec<Value> = ErrorCode::ALL
+        .iter()
+        .map(|code| {
+            json!({
+                "id": code.as_str(),
+                "name": format!("{code:?}"),
+                "shortDescription": { "text": code.title() },
+                "defaultConfiguration": {
+                    "level": if code.is_lint() { "warning" } else { "error" },
+                    "enabled": code.default_level() != Level::Allow,
+                },
+                "fullDescription": { "text": code.explanation() },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = errors
+        .iter()
+        .map(|err| {
+            let uri = file_name(err, sources);
+            let columns = Columns::of(err, sources, encoding);
+            let mut result = json!({
+                "ruleId": match err.code() {
+                    Some(code) => code.to_string(),
+                    None => err.err_type().to_string(),
+                },
+                "level": match err.severity() {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Note | Severity::Help => "note",
+                },
+                "message": { "text": err.message() },
+                "properties": {
+                    "type": err.err_type().to_string(),
+                    "notes": err.notes(),
+                    "help": err.helps(),
+                },
+            });
+            if let Some(code) = err.code() {
+                result["ruleIndex"] = json!(ErrorCode::ALL.iter().position(|c| *c == code));
+            }
+            if let Some(line) = err.line() {
+                let span = Span::new(line, err.column().unwrap_or(1), err.length().unwrap_or(1));
+                result["locations"] = json!([sarif_location(uri, &columns.span(&span))]);
+            } else if let Some(uri) = uri {
+                result["locations"] = json!([{
+                    "physicalLocation": { "artifactLocation": { "uri": uri } }
+                }]);
+            }
+            if let Some(uri) = uri
+                && !err.suggestions().is_empty()
+            {
+                result["fixes"] = err
+                    .suggestions()
+                    .iter()
+                    .map(|suggestion| sarif_fix(uri, suggestion, &columns))
+                    .collect();
+            }
+            result["relatedLocations"] = err
+                .labels()
+                .iter()
+                .enumerate()
+                .map(|(i, label)| {
+                    let mut location = sarif_location(uri, &columns.span(&label.span));
+                    location["id"] = json!(i);
+                    if let Some(msg) = &label.message {
+                        location["message"] = json!({ "text": msg });
+                    }
+                    location
+                })
+                .collect();
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "enoc",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "columnKind": match encoding {
+                PositionEncoding::Utf16 => "utf16CodeUnits",
+                _ => "unicodeCodePoints",
+            },
+            "results": results,
+        }]
+    })
+}
+
+/// Re-counts the char columns of one diagnostic's file in another encoding.
+struct Columns<'a> {
+    source: Option<&'a SourceFile>,
+    encoding: PositionEncoding,
+}
+
+impl Columns<'_> {
+    fn of<'a>(err: &Error, sources: &'a SourceMap, encoding: PositionEncoding) -> Columns<'a> {
+        Columns {
+            source: err.file().map(|id| sources.get(id)),
+            encoding,
+        }
+    }
+
+    fn column(&self, line: usize, column: usize) -> usize {
+        self.source
+            .and_then(|source| source.encode_column(line, column, self.encoding))
+            .unwrap_or(column)
+    }
+
+    fn span(&self, span: &Span) -> Span {
+        Span::multiline(
+            span.line,
+            self.column(span.line, span.column),
+            span.end_line,
+            self.column(span.end_line, span.end_column),
+        )
+    }
+
+    /// The column and length of the diagnostic itself.
+    fn position(&self, err: &Error) -> (Option<usize>, Option<usize>) {
+        let (Some(line), Some(column)) = (err.line(), err.column()) else {
+            return (err.column(), err.length());
+        };
+        let start = self.column(line, column);
+        let length = err
+            .length()
+            .map(|length| self.column(line, column + length).saturating_sub(start));
+        (Some(start), length)
+    }
+}
+
+fn label_json(label: &Label, columns: &Columns) -> Value {
+    let span = columns.span(&label.span);
+    json!({
+        "line": span.line,
+        "column": span.column,
+        "end_line": span.end_line,
+        "end_column": span.end_column,
+        "message": label.message,
+        "primary": label.primary,
+    })
+}
+
+fn suggestion_json(suggestion: &Suggestion, columns: &Columns) -> Value {
+    let span = columns.span(&suggestion.span);
+    json!({
+        "line": span.line,
+        "column": span.column,
+        "end_line": span.end_line,
+        "end_column": span.end_column,
+        "replacement": suggestion.replacement,
+        "message": suggestion.message,
+        "applicability": match suggestion.applicability {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+        },
+    })
+}
+
+fn sarif_fix(uri: &str, suggestion: &Suggestion, columns: &Columns) -> Value {
+    let span = columns.span(&suggestion.span);
+    json!({
+        "description": { "text": suggestion.message },
+        "artifactChanges": [{
+            "artifactLocation": { "uri": uri },
+            "replacements": [{
+                "deletedRegion": {
+                    "startLine": span.line,
+                    "startColumn": span.column,
+                    "endLine": span.end_line,
+                    "endColumn": span.end_column,
+                },
+                "insertedContent": { "text": suggestion.replacement },
+            }],
+        }],
+    })
+}
+
+fn sarif_location(uri: Option<&str>, span: &Span) -> Value {
+    let mut location = json!({
+        "physicalLocation": {
+            "region": {
+                "startLine": span.line,
+                "startColumn": span.column,
+                "endLine": span.end_line,
+                "endColumn": span.end_column,
+            }
+        }
+    });
+    if let Some(uri) = uri {
+        location["physicalLocation"]["artifactLocation"] = json!({ "uri": uri });
+    }
+    location
+}
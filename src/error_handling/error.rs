@@ -1,11 +1,17 @@
 use std::error::Error as StdError;
 use std::fmt::{Debug, Display};
+use std::io;
 use std::result::Result as StdResult;
 pub type Result<T> = StdResult<T, Error>;
-use crate::modules::file_reader::take_line_from_source;
+use crate::modules::source_map::{FileId, SourceMap};
 
-use super::error_path;
+use super::codes::ErrorCode;
+use super::diagnostics::Severity;
+use super::label::Label;
+use super::renderer::Renderer;
+use super::suggestion::Suggestion;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorType {
     MissingArgument,
     IoError,
@@ -27,63 +33,186 @@ impl Display for ErrorType {
 }
 
 pub struct Error {
+    // boxed so that `Result<T>` stays small on the happy path
+    inner: Box<ErrorInner>,
+}
+
+struct ErrorInner {
     err_type: ErrorType,
+    severity: Severity,
+    code: Option<ErrorCode>,
     message: Option<String>,
     line: Option<usize>,
     column: Option<usize>,
-    file_path: Option<String>,
+    file: Option<FileId>,
+    // for errors about a file that never made it into the source map
+    path: Option<String>,
     length: Option<usize>,
-    //source: Option<Box<dyn StdError + Send + Sync>>,
+    labels: Vec<Label>,
+    notes: Vec<String>,
+    helps: Vec<String>,
+    suggestions: Vec<Suggestion>,
+    source: Option<Box<dyn StdError + Send + Sync>>,
 }
 
 impl Error {
     pub fn new(err_typ: ErrorType) -> Error {
-        Error {
+        let inner = ErrorInner {
             err_type: err_typ,
+            severity: Severity::Error,
+            code: None,
             message: None,
             line: None,
             column: None,
-            file_path: None,
+            file: None,
+            path: None,
             length: None,
-            //source: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            helps: Vec::new(),
+            suggestions: Vec::new(),
+            source: None,
+        };
+        Error {
+            inner: Box::new(inner),
         }
     }
+    /// An `IoError` about `path`, keeping `err` as its source.
+    pub fn io(err: io::Error, path: &str) -> Error {
+        let help = match err.kind() {
+            io::ErrorKind::NotFound => Some("check that the path exists"),
+            io::ErrorKind::PermissionDenied => Some("check the permissions of the file"),
+            io::ErrorKind::InvalidData => Some("source files must be valid UTF-8"),
+            _ => None,
+        };
+        let mut e = Error::new(ErrorType::IoError)
+            .with_code(ErrorCode::Io)
+            .with_message(format!("{err} -> '{path}'"))
+            .with_path(path);
+        if let Some(help) = help {
+            e = e.with_help(help);
+        }
+        e.with_source(err)
+    }
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.inner.severity = severity;
+        self
+    }
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.inner.code = Some(code);
+        self
+    }
     pub fn with_message(mut self, msg: String) -> Self {
-        self.message = Some(msg);
+        self.inner.message = Some(msg);
         self
     }
 
     pub fn with_line(mut self, lin: usize) -> Self {
-        self.line = Some(lin);
+        self.inner.line = Some(lin);
         self
     }
     pub fn with_column(mut self, col: usize) -> Self {
-        self.column = Some(col);
+        self.inner.column = Some(col);
+        self
+    }
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.inner.file = Some(file);
         self
     }
-    pub fn with_file_path(mut self, path: &str) -> Self {
-        self.file_path = Some(path.to_owned());
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.inner.path = Some(path.to_owned());
+        self
+    }
+    /// The underlying cause, returned by `std::error::Error::source`.
+    pub fn with_source(mut self, source: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        self.inner.source = Some(source.into());
         self
     }
     pub fn with_length(mut self, len: usize) -> Self {
-        self.length = Some(len);
+        self.inner.length = Some(len);
+        self
+    }
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.inner.labels.push(label);
+        self
+    }
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.inner.notes.push(note.to_owned());
+        self
+    }
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.inner.helps.push(help.to_owned());
+        self
+    }
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.inner.suggestions.push(suggestion);
         self
     }
-    fn message(&self) -> String {
-        match &self.message {
+
+    /// The full diagnostic with its source snippet, as plain text without colours.
+    pub fn render(&self, sources: &SourceMap) -> String {
+        Renderer::new().with_color(false).render(self, sources)
+    }
+
+    pub fn err_type(&self) -> &ErrorType {
+        &self.inner.err_type
+    }
+    pub fn severity(&self) -> Severity {
+        self.inner.severity
+    }
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.inner.code
+    }
+    pub fn message(&self) -> String {
+        match &self.inner.message {
             Some(msg) => msg.clone(),
             _ => "An error occured!".to_string(),
         }
     }
+    pub fn line(&self) -> Option<usize> {
+        self.inner.line
+    }
+    pub fn column(&self) -> Option<usize> {
+        self.inner.column
+    }
+    pub fn length(&self) -> Option<usize> {
+        self.inner.length
+    }
+    pub fn file(&self) -> Option<FileId> {
+        self.inner.file
+    }
+    pub fn path(&self) -> Option<&str> {
+        self.inner.path.as_deref()
+    }
+    /// The kind of the `io::Error` this error was caused by, if any.
+    pub fn io_kind(&self) -> Option<io::ErrorKind> {
+        self.inner
+            .source
+            .as_deref()
+            .and_then(|source| source.downcast_ref::<io::Error>())
+            .map(io::Error::kind)
+    }
+    pub fn labels(&self) -> &[Label] {
+        &self.inner.labels
+    }
+    pub fn notes(&self) -> &[String] {
+        &self.inner.notes
+    }
+    pub fn helps(&self) -> &[String] {
+        &self.inner.helps
+    }
+    pub fn suggestions(&self) -> &[Suggestion] {
+        &self.inner.suggestions
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}, {}", self.err_type, self.message())?;
-        if let Some(line) = self.line {
+        write!(f, "{}, {}", self.inner.err_type, self.message())?;
+        if let Some(line) = self.inner.line {
             write!(f, " at line {}", line)?;
         }
-        if let Some(column) = self.column {
+        if let Some(column) = self.inner.column {
             write!(f, ", column {}", column)?;
         }
         Ok(())
@@ -91,63 +220,39 @@ impl Display for Error {
 }
 impl Debug for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "\n  Type: {}\n  Message: {}",
-            self.err_type,
-            self.message()
-        )?;
-        if let Some(path) = self.file_path.clone() {
-            let this_path = error_path::path(&path);
-            write!(f, "\n  file_path: '{}'", this_path)?;
-        }
-        if let Some(line) = self.line {
-            write!(f, "\n  Line: {}", line)?;
-        }
-        if let Some(column) = self.column {
-            write!(f, "\n  Column: {}", column)?;
-        }
-        if let Some(length) = self.length {
-            write!(f, "\n  Length: {}", length)?;
-        }
-        let line = match self.line {
-            Some(l) => l,
-            None => 1,
-        };
-        let fpath = match &self.file_path {
-            Some(p) => p,
-            None => &String::new(),
-        };
-        let line_data = match take_line_from_source(fpath, line) {
-            Ok(data) => data,
-            Err(e) => format!("Error reading line: {}", e),
-        };
-        let col = match self.column {
-            Some(c) => c,
-            None => 0,
-        };
-        println!("\n {} | {}", line, line_data);
-        for _ in 0..col + 4 {
-            print!(" ");
-        }
-        let len = match self.length {
-            Some(l) => l,
-            None => 0,
-        };
-        for _ in 0..len {
-            print!("^");
-        }
-        print!("\n");
-        Ok(())
+        f.debug_struct("Error")
+            .field("type", &self.inner.err_type)
+            .field("severity", &self.inner.severity)
+            .field("code", &self.inner.code)
+            .field("message", &self.message())
+            .field("file", &self.inner.file)
+            .field("path", &self.inner.path)
+            .field("line", &self.inner.line)
+            .field("column", &self.inner.column)
+            .field("length", &self.inner.length)
+            .field("labels", &self.inner.labels)
+            .field("notes", &self.inner.notes)
+            .field("helps", &self.inner.helps)
+            .field("suggestions", &self.inner.suggestions)
+            .field("source", &self.inner.source)
+            .finish()
     }
 }
 
-impl StdError for Error {}
-
-use std::io;
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.inner
+            .source
+            .as_deref()
+            .map(|source| source as &(dyn StdError + 'static))
+    }
+}
 
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
-        Error::new(ErrorType::IoError).with_message(e.to_string())
+        Error::new(ErrorType::IoError)
+            .with_code(ErrorCode::Io)
+            .with_message(e.to_string())
+            .with_source(e)
     }
 }
@@ -0,0 +1,59 @@
+/// A region of the source, 1-based like the lexer positions. `end_column` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, length: usize) -> Span {
+        Span {
+            line,
+            column,
+            end_line: line,
+            end_column: column + length,
+        }
+    }
+    pub fn multiline(line: usize, column: usize, end_line: usize, end_column: usize) -> Span {
+        Span {
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+    pub fn is_multiline(&self) -> bool {
+        self.end_line > self.line
+    }
+}
+
+/// A span with an optional message, drawn under the source snippet of a diagnostic.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: Option<String>,
+    pub primary: bool,
+}
+
+impl Label {
+    pub fn primary(span: Span) -> Label {
+        Label {
+            span,
+            message: None,
+            primary: true,
+        }
+    }
+    pub fn secondary(span: Span) -> Label {
+        Label {
+            span,
+            message: None,
+            primary: false,
+        }
+    }
+    pub fn with_message(mut self, msg: &str) -> Self {
+        self.message = Some(msg.to_owned());
+        self
+    }
+}
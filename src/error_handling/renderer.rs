@@ -0,0 +1,282 @@
+use std::io::IsTerminal;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::diagnostics::Severity;
+use super::error::Error;
+use super::error_path;
+use super::label::{Label, Span};
+use crate::modules::position::PositionEncoding;
+use crate::modules::source_map::SourceMap;
+
+const RED: &str = "\x1b[31;1m";
+const YELLOW: &str = "\x1b[33;1m";
+const GREEN: &str = "\x1b[32;1m";
+const BLUE: &str = "\x1b[34;1m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Spans longer than this many lines are shown with their middle elided.
+const MAX_SPAN_LINES: usize = 4;
+
+/// Turns an `Error` into a human readable diagnostic with a source snippet.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    color: bool,
+    tab_width: usize,
+    encoding: PositionEncoding,
+}
+
+impl Default for Renderer {
+    fn default() -> Renderer {
+        Renderer {
+            color: std::env::var_os("NO_COLOR").is_none() && std::io::stderr().is_terminal(),
+            tab_width: 4,
+            encoding: PositionEncoding::default(),
+        }
+    }
+}
+
+impl Renderer {
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn with_tab_width(mut self, width: usize) -> Self {
+        self.tab_width = width.max(1);
+        self
+    }
+    /// The unit of the column in the ` --> file:line:column` header.
+    pub fn with_position_encoding(mut self, encoding: PositionEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Renders `err` with a snippet of the source it points into.
+    pub fn render(&self, err: &Error, sources: &SourceMap) -> String {
+        let file = err.file().map(|id| sources.get(id));
+        let mut out = format!(
+            "{}: {}\n",
+            self.paint(
+                severity_color(err.severity()),
+                &format!("{}[{}]", err.severity(), code_of(err))
+            ),
+            self.paint(BOLD, &err.message())
+        );
+
+        let labels = self.labels_of(err);
+        let shown = shown_lines(&labels);
+        let gutter = shown.last().map_or(1, |l| l.to_string().len());
+        let pad = " ".repeat(gutter);
+
+        if let Some(name) = file.map(|f| f.name()).or(err.path()) {
+            out.push_str(&format!(
+                "{pad}{} {}",
+                self.paint(BLUE, "-->"),
+                error_path::path(name)
+            ));
+            if let Some(line) = err.line() {
+                let column = err.column().unwrap_or(1);
+                let column = file
+                    .and_then(|f| f.encode_column(line, column, self.encoding))
+                    .unwrap_or(column);
+                out.push_str(&format!(":{line}:{column}"));
+            }
+            out.push('\n');
+        }
+
+        if let Some(file) = file.filter(|_| !labels.is_empty()) {
+            out.push_str(&format!("{pad} {}\n", self.paint(BLUE, "|")));
+            let mut last_shown = None;
+            for &line_no in &shown {
+                if last_shown.is_some_and(|l| line_no > l + 1) {
+                    out.push_str(&format!("{}\n", self.paint(BLUE, "...")));
+                }
+                last_shown = Some(line_no);
+
+                let text = file.line(line_no).unwrap_or("");
+                let layout = LineLayout::new(text, self.tab_width);
+                out.push_str(&format!(
+                    "{} {} {}\n",
+                    self.paint(BLUE, &format!("{line_no:>gutter$}")),
+                    self.paint(BLUE, "|"),
+                    layout.expanded
+                ));
+                for label in labels.iter().filter(|l| covers(&l.span, line_no)) {
+                    out.push_str(&format!(
+                        "{pad} {} {}\n",
+                        self.paint(BLUE, "|"),
+                        self.underline(label, line_no, text, &layout, err.severity())
+                    ));
+                }
+            }
+        }
+
+        if !err.notes().is_empty() || !err.helps().is_empty() || !err.suggestions().is_empty() {
+            out.push_str(&format!("{pad} {}\n", self.paint(BLUE, "|")));
+        }
+        for note in err.notes() {
+            out.push_str(&format!("{pad} {} {note}\n", self.paint(BLUE, "= note:")));
+        }
+        for help in err.helps() {
+            out.push_str(&format!("{pad} {} {help}\n", self.paint(BLUE, "= help:")));
+        }
+        for suggestion in err.suggestions() {
+            let edit = match suggestion.replacement.as_str() {
+                "" => String::new(),
+                text => format!(": `{text}`"),
+            };
+            out.push_str(&format!(
+                "{pad} {} {}{edit}\n",
+                self.paint(BLUE, "= help:"),
+                suggestion.message
+            ));
+        }
+        if let Some(code) = err.code() {
+            out.push_str(&format!(
+                "\nFor more information about this {}, try `enoc --explain {code}`.\n",
+                err.severity()
+            ));
+        }
+        out
+    }
+
+    /// The labels of `err`, with the primary one made up from its position if it has none.
+    fn labels_of(&self, err: &Error) -> Vec<Label> {
+        let mut labels = err.labels().to_vec();
+        if !labels.iter().any(|l| l.primary)
+            && let Some(line) = err.line()
+        {
+            let span = Span::new(line, err.column().unwrap_or(1), err.length().unwrap_or(1));
+            labels.insert(0, Label::primary(span));
+        }
+        labels.sort_by_key(|l| (l.span.line, l.span.column));
+        labels
+    }
+
+    fn underline(
+        &self,
+        label: &Label,
+        line_no: usize,
+        text: &str,
+        layout: &LineLayout,
+        severity: Severity,
+    ) -> String {
+        let span = &label.span;
+        let line_len = text.chars().count();
+        let start = if line_no == span.line {
+            span.column
+        } else {
+            // continuation lines are underlined from their first non-blank character
+            text.chars().take_while(|c| c.is_whitespace()).count() + 1
+        };
+        let end = if line_no == span.end_line {
+            span.end_column
+        } else {
+            line_len + 1
+        };
+
+        let from = layout.display_column(start);
+        let to = layout.display_column(end.max(start + 1)).max(from + 1);
+        let (mark, color) = if label.primary {
+            ("^", severity_color(severity))
+        } else {
+            ("-", BLUE)
+        };
+        let mut body = mark.repeat(to - from);
+        if line_no == span.end_line
+            && let Some(msg) = &label.message
+        {
+            body.push(' ');
+            body.push_str(msg);
+        }
+        format!("{}{}", " ".repeat(from), self.paint(color, &body))
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.color {
+            format!("{color}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// A source line with tabs expanded, plus where each char starts on screen.
+///
+/// Widths are measured per grapheme cluster, so combining marks and emoji sequences take up
+/// the cells a terminal gives them; every char of a cluster starts where the cluster does.
+struct LineLayout {
+    expanded: String,
+    starts: Vec<usize>,
+}
+
+impl LineLayout {
+    fn new(text: &str, tab_width: usize) -> LineLayout {
+        let mut expanded = String::new();
+        let mut starts = Vec::new();
+        let mut width = 0;
+        for grapheme in text.graphemes(true) {
+            starts.extend(grapheme.chars().map(|_| width));
+            if grapheme == "\t" {
+                let next = (width / tab_width + 1) * tab_width;
+                expanded.push_str(&" ".repeat(next - width));
+                width = next;
+            } else {
+                expanded.push_str(grapheme);
+                width += grapheme.width();
+            }
+        }
+        starts.push(width);
+        LineLayout { expanded, starts }
+    }
+
+    /// Screen offset of the 1-based char column; columns past the end keep counting up.
+    fn display_column(&self, column: usize) -> usize {
+        let index = column.saturating_sub(1);
+        match self.starts.get(index) {
+            Some(start) => *start,
+            None => self.starts[self.starts.len() - 1] + index + 1 - self.starts.len(),
+        }
+    }
+}
+
+fn severity_color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => RED,
+        Severity::Warning => YELLOW,
+        Severity::Note | Severity::Help => GREEN,
+    }
+}
+
+/// The stable code if the error has one, its broad type otherwise.
+fn code_of(err: &Error) -> String {
+    match err.code() {
+        Some(code) => code.to_string(),
+        None => err.err_type().to_string(),
+    }
+}
+
+fn covers(span: &Span, line: usize) -> bool {
+    span.line <= line && line <= span.end_line
+}
+
+/// Every line drawn in the snippet, in order; long spans only show their ends.
+fn shown_lines(labels: &[Label]) -> Vec<usize> {
+    let mut lines: Vec<usize> = Vec::new();
+    for label in labels {
+        let span = &label.span;
+        if span.end_line - span.line < MAX_SPAN_LINES {
+            lines.extend(span.line..=span.end_line);
+        } else {
+            lines.extend([span.line, span.line + 1, span.end_line]);
+        }
+    }
+    lines.sort_unstable();
+    lines.dedup();
+    lines
+}
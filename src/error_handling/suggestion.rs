@@ -0,0 +1,32 @@
+use super::label::Span;
+
+/// How sure a suggestion is; only machine-applicable ones are applied by `enoc fix`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    MachineApplicable,
+    MaybeIncorrect,
+}
+
+/// A proposed edit: replace the text of `span` with `replacement`. An empty span inserts.
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub span: Span,
+    pub replacement: String,
+    pub message: String,
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    pub fn new(span: Span, replacement: &str, message: &str) -> Suggestion {
+        Suggestion {
+            span,
+            replacement: replacement.to_owned(),
+            message: message.to_owned(),
+            applicability: Applicability::MachineApplicable,
+        }
+    }
+    pub fn maybe_incorrect(mut self) -> Self {
+        self.applicability = Applicability::MaybeIncorrect;
+        self
+    }
+}
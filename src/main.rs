@@ -1,21 +1,95 @@
-use lexer::error_handling::Result;
+mod commands;
+
+use std::process::ExitCode;
+
+use lexer::error_handling::{Diagnostics, ErrorCode, ErrorFormat, Level, Renderer, Result};
 use lexer::modules::{
-    analyser::analyze_chars, args_handler::parse_args, file_reader::take_sources_as_string,
-    tokenizer::tokenize,
+    analyser::{AnalyzeOptions, analyze_chars_with},
+    args_handler::{Args, parse_args},
+    lints::lint,
+    source_map::SourceMap,
+    tokenizer::{TokenizeOptions, tokenize_with},
 };
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let args = parse_args();
+    let mut sources = SourceMap::new();
+    let mut diagnostics = diagnostics_for(&args);
+
+    let code = match run(&args, &mut sources, &mut diagnostics) {
+        Ok(code) => code,
+        Err(err) => {
+            diagnostics.push(err);
+            ExitCode::FAILURE
+        }
+    };
+
+    let format = args
+        .get_arg("error-format")
+        .ok()
+        .and_then(|name| ErrorFormat::from_name(&name))
+        .unwrap_or(ErrorFormat::Human);
+    let encoding = args.get_position_encoding();
+    let renderer = Renderer::new()
+        .with_tab_width(args.get_tab_width())
+        .with_position_encoding(encoding);
+    eprint!(
+        "{}",
+        format.emit(diagnostics.diagnostics(), &renderer, &sources, encoding)
+    );
+
+    if diagnostics.has_errors() {
+        ExitCode::FAILURE
+    } else {
+        code
+    }
+}
+
+fn diagnostics_for(args: &Args) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new().with_deny_warnings(args.get_flag("deny-warnings"));
+    for (arg, level) in [("warn", Level::Warn), ("allow", Level::Allow)] {
+        for code in args.get_many(arg) {
+            if let Some(code) = ErrorCode::from_code(&code) {
+                diagnostics = diagnostics.with_level(code, level);
+            }
+        }
+    }
+    diagnostics
+}
+
+fn run(args: &Args, sources: &mut SourceMap, diagnostics: &mut Diagnostics) -> Result<ExitCode> {
+    match args.get_subcommand() {
+        Some(("fmt", fmt_args)) => return commands::fmt(&fmt_args, sources, diagnostics),
+        Some(("fix", fix_args)) => return commands::fix(&fix_args, sources, diagnostics),
+        Some(("doc", doc_args)) => return commands::doc(&doc_args, sources),
+        Some(("grammar", grammar_args)) => return commands::grammar(&grammar_args),
+        Some(("lex", lex_args)) => return commands::lex(&lex_args, sources, diagnostics),
+        _ => {}
+    }
+    if let Ok(code) = args.get_arg("explain") {
+        return Ok(commands::explain(&code));
+    }
 
     let source_path = args.get_source_path()?;
+    let file = sources.load(&source_path)?;
 
-    let chars = analyze_chars(&take_sources_as_string(&source_path)?);
+    let options = AnalyzeOptions::new().with_tab_width(args.get_tab_width());
+    let chars = analyze_chars_with(sources.get(file).text(), &options);
 
-    let tokens = tokenize(&chars, &source_path)?;
+    let tokenize_options = TokenizeOptions::new().with_language(args.get_language()?);
+    let tokens = tokenize_with(&chars, file, &tokenize_options)?;
 
     tokens.iter().for_each(|token| {
-        println!("{:?}", token);
+        println!("{:?}", tokens.interner().resolved(token));
     });
 
-    Ok(())
+    lint(
+        file,
+        sources.get(file),
+        &tokens,
+        tokenize_options.language(),
+        diagnostics,
+    );
+
+    Ok(ExitCode::SUCCESS)
 }
@@ -1,6 +1,17 @@
 pub mod analyser;
 pub mod args_handler;
+pub mod batch;
+pub mod docs;
 pub mod file_reader;
+pub mod fixer;
+pub mod formatter;
+pub mod grammar;
+pub mod incremental;
+pub mod interner;
+pub mod language;
+pub mod lints;
+pub mod position;
+pub mod source_map;
 pub mod token_stream;
 pub mod tokenizer;
 pub mod tokens;
@@ -1,7 +1,8 @@
 #[derive(Debug)]
 pub enum CharKind {
-    Whitespace,
+    Whitespace(char),
     Char(char),
+    /// One line break, whether it was written as `\n`, `\r\n` or a lone `\r`.
     Newline,
 }
 
@@ -10,25 +11,81 @@ pub struct AnalyzedChar {
     pub kind: CharKind,
     pub line: usize,
     pub column: usize,
+    /// Column as shown in an editor, with tabs expanded to the next tab stop.
+    pub visual_column: usize,
+    /// Byte offset in the input, not counting a leading byte order mark.
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalyzeOptions {
+    tab_width: usize,
+}
+
+impl Default for AnalyzeOptions {
+    fn default() -> Self {
+        AnalyzeOptions { tab_width: 4 }
+    }
+}
+
+impl AnalyzeOptions {
+    pub fn new() -> AnalyzeOptions {
+        AnalyzeOptions::default()
+    }
+    /// Distance between tab stops used for `visual_column`.
+    pub fn with_tab_width(mut self, width: usize) -> Self {
+        self.tab_width = width.max(1);
+        self
+    }
 }
 
 pub fn analyze_chars(input: &str) -> Vec<AnalyzedChar> {
+    analyze_chars_with(input, &AnalyzeOptions::default())
+}
+
+pub fn analyze_chars_with(input: &str, options: &AnalyzeOptions) -> Vec<AnalyzedChar> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
     let mut result = Vec::new();
     let mut line = 1;
     let mut column = 1;
+    let mut visual_column = 1;
     //let mut comment_one = false;
-    for ch in input.chars() {
+    let mut iter = input.char_indices().peekable();
+    while let Some((offset, ch)) = iter.next() {
         let kind = match ch {
             '\n' => CharKind::Newline,
-            c if c.is_whitespace() => CharKind::Whitespace,
+            '\r' => {
+                // `\r\n` is a single line break
+                if iter.peek().is_some_and(|(_, c)| *c == '\n') {
+                    iter.next();
+                }
+                CharKind::Newline
+            }
+            c if c.is_whitespace() => CharKind::Whitespace(c),
             c => CharKind::Char(c),
         };
-        result.push(AnalyzedChar { kind, line, column });
-        if ch == '\n' {
+        let is_newline = matches!(kind, CharKind::Newline);
+        result.push(AnalyzedChar {
+            kind,
+            line,
+            column,
+            visual_column,
+            offset,
+        });
+        if is_newline {
             line += 1;
             column = 1;
+            visual_column = 1;
         } else {
             column += 1;
+            visual_column = match ch {
+                '\t' => {
+                    (visual_column - 1) / options.tab_width * options.tab_width
+                        + options.tab_width
+                        + 1
+                }
+                _ => visual_column + 1,
+            };
         }
     }
     result
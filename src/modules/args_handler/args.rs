@@ -1,4 +1,6 @@
-use crate::error_handling::{Error, ErrorType};
+use crate::error_handling::{Error, ErrorCode, ErrorType};
+use crate::modules::language::LanguageSpec;
+use crate::modules::position::PositionEncoding;
 use clap::ArgMatches;
 
 pub struct Args {
@@ -15,7 +17,56 @@ impl Args {
     pub fn get_arg(&self, arg_title: &str) -> Result<String, Error> {
         match self.args.get_one::<String>(arg_title) {
             Some(arg) => Ok(arg.to_string()),
-            _ => Err(Error::new(ErrorType::MissingArgument)),
+            _ => Err(Error::new(ErrorType::MissingArgument)
+                .with_code(ErrorCode::MissingArgument)
+                .with_message(format!("Missing argument '--{arg_title}'"))),
+        }
+    }
+
+    pub fn get_flag(&self, arg_title: &str) -> bool {
+        self.args.get_flag(arg_title)
+    }
+
+    pub fn get_many(&self, arg_title: &str) -> Vec<String> {
+        match self.args.get_many::<String>(arg_title) {
+            Some(values) => values.cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn get_subcommand(&self) -> Option<(&str, Args)> {
+        self.args
+            .subcommand()
+            .map(|(name, matches)| (name, Args::new(matches.clone())))
+    }
+
+    pub fn get_tab_width(&self) -> usize {
+        self.get_arg("tab-width")
+            .ok()
+            .and_then(|width| width.parse().ok())
+            .unwrap_or(4)
+    }
+
+    /// The threads given with `-j`, 0 for one per CPU.
+    pub fn get_jobs(&self) -> usize {
+        self.get_arg("jobs")
+            .ok()
+            .and_then(|jobs| jobs.parse().ok())
+            .unwrap_or(0)
+    }
+
+    pub fn get_position_encoding(&self) -> PositionEncoding {
+        self.get_arg("position-encoding")
+            .ok()
+            .and_then(|name| PositionEncoding::from_name(&name))
+            .unwrap_or_default()
+    }
+
+    /// The language given with `--language`, or the built-in `.lx` one.
+    pub fn get_language(&self) -> Result<LanguageSpec, Error> {
+        match self.get_arg("language") {
+            Ok(path) => LanguageSpec::load(&path),
+            Err(_) => Ok(LanguageSpec::default()),
         }
     }
 
@@ -1,5 +1,6 @@
 use super::args::Args;
-use clap::{Arg, ArgGroup, Command};
+use crate::error_handling::ErrorCode;
+use clap::{Arg, ArgAction, ArgGroup, Command};
 
 pub fn parse_args() -> Args {
     Args::new(
@@ -9,11 +10,178 @@ pub fn parse_args() -> Args {
             .about("lexer")
             .arg(Arg::new("source").short('s').long("source"))
             .arg(Arg::new("example").short('e').long("example"))
+            .arg(
+                Arg::new("explain")
+                    .long("explain")
+                    .value_name("CODE")
+                    .help("print the long explanation of an error code such as L0001"),
+            )
+            .arg(
+                Arg::new("error-format")
+                    .long("error-format")
+                    .global(true)
+                    .value_parser(["human", "json", "sarif"])
+                    .default_value("human")
+                    .help("how diagnostics are written to stderr"),
+            )
+            .arg(
+                Arg::new("position-encoding")
+                    .long("position-encoding")
+                    .global(true)
+                    .value_parser(["utf-8", "utf-16", "utf-32"])
+                    .default_value("utf-32")
+                    .help("count diagnostic columns in UTF-8 bytes, UTF-16 units or chars"),
+            )
+            .arg(
+                Arg::new("tab-width")
+                    .long("tab-width")
+                    .global(true)
+                    .value_parser(tab_width)
+                    .default_value("4")
+                    .help("columns between tab stops when showing source lines"),
+            )
+            .arg(
+                Arg::new("language")
+                    .long("language")
+                    .value_name("SPEC")
+                    .global(true)
+                    .help("lex the language defined in the TOML file SPEC instead of .lx"),
+            )
+            .arg(
+                Arg::new("warn")
+                    .short('W')
+                    .value_name("CODE")
+                    .action(ArgAction::Append)
+                    .value_parser(lint_code)
+                    .global(true)
+                    .help("report the lint CODE as a warning"),
+            )
+            .arg(
+                Arg::new("allow")
+                    .short('A')
+                    .value_name("CODE")
+                    .action(ArgAction::Append)
+                    .value_parser(lint_code)
+                    .global(true)
+                    .help("do not report the lint CODE"),
+            )
+            .arg(
+                Arg::new("deny-warnings")
+                    .long("deny-warnings")
+                    .action(ArgAction::SetTrue)
+                    .global(true)
+                    .help("turn every reported warning into an error"),
+            )
             .group(
                 ArgGroup::new("input")
-                    .args(["source", "example"])
+                    .args(["source", "example", "explain"])
                     .required(true),
             )
+            .subcommand_negates_reqs(true)
+            .subcommand(
+                Command::new("fmt")
+                    .about("formats .lx source files in place")
+                    .arg(Arg::new("files").num_args(1..).required(true))
+                    .arg(
+                        Arg::new("check")
+                            .long("check")
+                            .action(ArgAction::SetTrue)
+                            .help("only report unformatted files with a diff, exit 1 if any"),
+                    ),
+            )
+            .subcommand(
+                Command::new("lex")
+                    .about("lexes many .lx files, or all of them under a directory, in parallel")
+                    .arg(Arg::new("paths").num_args(1..).required(true))
+                    .arg(
+                        Arg::new("jobs")
+                            .short('j')
+                            .long("jobs")
+                            .value_name("N")
+                            .value_parser(jobs)
+                            .default_value("0")
+                            .help("lex on N threads, 0 for one per CPU"),
+                    )
+                    .arg(
+                        Arg::new("extension")
+                            .long("extension")
+                            .default_value("lx")
+                            .help("extension of the files lexed under a directory"),
+                    ),
+            )
+            .subcommand(
+                Command::new("fix")
+                    .about("applies machine-applicable suggestions to .lx files in place")
+                    .arg(Arg::new("files").num_args(1..).required(true)),
+            )
+            .subcommand(
+                Command::new("doc")
+                    .about("generates API documentation from the doc comments of .lx files")
+                    .arg(Arg::new("files").num_args(1..).required(true))
+                    .arg(
+                        Arg::new("format")
+                            .long("format")
+                            .value_parser(["markdown", "html"])
+                            .default_value("markdown")
+                            .help("write Markdown or a standalone HTML page"),
+                    )
+                    .arg(
+                        Arg::new("output")
+                            .short('o')
+                            .long("output")
+                            .value_name("DIR")
+                            .help("write one <file>.md or <file>.html per input into DIR instead of stdout"),
+                    ),
+            )
+            .subcommand(
+                Command::new("grammar")
+                    .about("generates TextMate, Vim and Sublime syntax highlighting for the language")
+                    .arg(
+                        Arg::new("format")
+                            .long("format")
+                            .value_parser(["all", "textmate", "vim", "sublime"])
+                            .default_value("all")
+                            .help("which editor grammar to write"),
+                    )
+                    .arg(
+                        Arg::new("name")
+                            .long("name")
+                            .default_value("lx")
+                            .help("language name used for file names and scopes"),
+                    )
+                    .arg(
+                        Arg::new("output")
+                            .short('o')
+                            .long("output")
+                            .value_name("DIR")
+                            .default_value(".")
+                            .help("directory the grammar files are written to"),
+                    ),
+            )
             .get_matches(),
     )
 }
+
+fn lint_code(code: &str) -> Result<String, String> {
+    match ErrorCode::from_code(code) {
+        Some(code) if code.is_lint() => Ok(code.to_string()),
+        Some(code) => Err(format!(
+            "{code} is an error, only lints (W....) can be configured"
+        )),
+        None => Err(format!("unknown lint code '{code}'")),
+    }
+}
+
+fn jobs(jobs: &str) -> Result<String, String> {
+    match jobs.parse::<usize>() {
+        Ok(_) => Ok(jobs.to_string()),
+        _ => Err(format!("'{jobs}' is not a number of threads")),
+    }
+}
+
+fn tab_width(width: &str) -> Result<String, String> {
+    match width.parse::<usize>() {
+        Ok(1..=16) => Ok(width.to_string()),
+        _ => Err(format!("'{width}' is not a tab width between 1 and 16")),
+    }
+}
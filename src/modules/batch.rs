@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use super::analyser::{AnalyzeOptions, analyze_chars_with};
+use super::file_reader::take_sources_as_string;
+use super::source_map::{FileId, SourceMap};
+use super::token_stream::TokenStream;
+use super::tokenizer::{TokenizeOptions, tokenize_with};
+use crate::error_handling::{Error, ErrorCode, ErrorType, Result};
+
+/// How `lex_files_with` reads and lexes its files.
+#[derive(Debug, Clone, Default)]
+pub struct BatchOptions {
+    jobs: usize,
+    analyze: AnalyzeOptions,
+    tokenize: TokenizeOptions,
+}
+
+impl BatchOptions {
+    pub fn new() -> BatchOptions {
+        BatchOptions::default()
+    }
+    /// Lex on `jobs` threads; 0, the default, is one per CPU.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+    pub fn with_analyze_options(mut self, options: AnalyzeOptions) -> Self {
+        self.analyze = options;
+        self
+    }
+    pub fn with_tokenize_options(mut self, options: TokenizeOptions) -> Self {
+        self.tokenize = options;
+        self
+    }
+}
+
+/// One input of `lex_files`: its tokens, or why it could not be read or lexed.
+pub struct LexedFile {
+    pub path: String,
+    pub result: Result<TokenStream>,
+}
+
+/// Totals over all files of a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub files: usize,
+    pub tokens: usize,
+    /// Files that could not be read or lexed.
+    pub errors: usize,
+    /// Source bytes of the files that were read.
+    pub bytes: usize,
+    /// Reading and lexing, from the first file to the last.
+    pub elapsed: Duration,
+}
+
+impl BatchSummary {
+    pub fn bytes_per_second(&self) -> f64 {
+        self.bytes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// The files of a batch in the order they were given, and their totals.
+pub struct Batch {
+    pub files: Vec<LexedFile>,
+    pub summary: BatchSummary,
+}
+
+impl Batch {
+    /// The error of every file that has one, in input order.
+    pub fn errors(&self) -> impl Iterator<Item = &Error> {
+        self.files.iter().filter_map(|f| f.result.as_ref().err())
+    }
+}
+
+/// The files `paths` name: files as given, and for directories every file under them
+/// ending in `.{extension}`, sorted by path.
+pub fn source_files(paths: &[String], extension: &str) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if Path::new(path).is_dir() {
+            let mut found = Vec::new();
+            walk(Path::new(path), extension, &mut found)?;
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn walk(dir: &Path, extension: &str, found: &mut Vec<String>) -> Result<()> {
+    let name = dir.display().to_string();
+    for entry in fs::read_dir(dir).map_err(|e| Error::io(e, &name))? {
+        let path = entry.map_err(|e| Error::io(e, &name))?.path();
+        if path.is_dir() {
+            walk(&path, extension, found)?;
+        } else if path.extension().is_some_and(|ext| ext == extension) {
+            found.push(path.display().to_string());
+        }
+    }
+    Ok(())
+}
+
+pub fn lex_files(sources: &mut SourceMap, paths: &[String]) -> Result<Batch> {
+    lex_files_with(sources, paths, &BatchOptions::default())
+}
+
+/// Reads and lexes `paths` on a thread pool, adding every file that could be read to
+/// `sources` in input order.
+///
+/// Results come back in the order of `paths` whatever the number of threads, so a file
+/// gets the same `FileId` and the errors are listed the same way on every run. Only
+/// starting the threads can fail as a whole.
+pub fn lex_files_with(
+    sources: &mut SourceMap,
+    paths: &[String],
+    options: &BatchOptions,
+) -> Result<Batch> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.jobs)
+        .build()
+        .map_err(|e| {
+            Error::new(ErrorType::IoError)
+                .with_code(ErrorCode::Io)
+                .with_message(format!("Could not start the lexing threads! -> {e}"))
+        })?;
+    let start = Instant::now();
+
+    let texts: Vec<Result<String>> = pool.install(|| {
+        paths
+            .par_iter()
+            .map(|path| take_sources_as_string(path).map_err(|e| Error::io(e, path)))
+            .collect()
+    });
+    let mut bytes = 0;
+    let loaded: Vec<Result<FileId>> = paths
+        .iter()
+        .zip(texts)
+        .map(|(path, text)| {
+            text.map(|text| {
+                bytes += text.len();
+                sources.add(path, text)
+            })
+        })
+        .collect();
+
+    let sources = &*sources;
+    let results: Vec<Result<TokenStream>> = pool.install(|| {
+        loaded
+            .into_par_iter()
+            .map(|file| {
+                let file = file?;
+                let chars = analyze_chars_with(sources.get(file).text(), &options.analyze);
+                tokenize_with(&chars, file, &options.tokenize)
+            })
+            .collect()
+    });
+
+    let mut summary = BatchSummary {
+        files: paths.len(),
+        tokens: 0,
+        errors: 0,
+        bytes,
+        elapsed: start.elapsed(),
+    };
+    let files = paths
+        .iter()
+        .zip(results)
+        .map(|(path, result)| {
+            match &result {
+                Ok(tokens) => summary.tokens += tokens.len(),
+                Err(_) => summary.errors += 1,
+            }
+            LexedFile {
+                path: path.clone(),
+                result,
+            }
+        })
+        .collect();
+    Ok(Batch { files, summary })
+}
@@ -0,0 +1,385 @@
+use super::interner::Interner;
+use super::token_stream::TokenStream;
+use super::tokens::{AnalyzedToken, Token};
+
+/// A name with its type, as in `a: i32`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemKind {
+    /// `op name(params) returns`; `returns` is `None` for `nret`.
+    Op {
+        params: Vec<Param>,
+        returns: Option<String>,
+    },
+    Data {
+        fields: Vec<Param>,
+    },
+    Let {
+        ty: Option<String>,
+    },
+}
+
+/// A top-level `op`, `data` or `let` with the `///` comments right above it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub name: String,
+    pub kind: ItemKind,
+    pub docs: String,
+    pub line: usize,
+}
+
+/// Everything `enoc doc` shows for one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleDocs {
+    /// Text of the `//!` comments.
+    pub docs: String,
+    pub items: Vec<Item>,
+}
+
+/// Attaches every run of `///` comments to the top-level item that follows it.
+///
+/// All top-level items are listed, documented or not. A doc comment followed by anything
+/// else, or inside a block, is dropped. Types are shown as they are written in `source`,
+/// the text `stream` was lexed from.
+pub fn collect_docs(source: &str, stream: &TokenStream) -> ModuleDocs {
+    let interner = stream.interner();
+    let tokens: Vec<&AnalyzedToken> = stream.iter().collect();
+    let mut module = Vec::new();
+    let mut pending: Vec<&str> = Vec::new();
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut pos = 0;
+
+    while pos < tokens.len() {
+        let token = tokens[pos];
+        // a contextual keyword only declares an item if a name follows, which `item` checks
+        let keyword = token.soft_keyword.as_ref().unwrap_or(&token.token);
+        match keyword {
+            Token::ModuleDocComment(text) => module.push(text.as_str()),
+            Token::DocComment(text) if depth == 0 => pending.push(text.as_str()),
+            Token::Op | Token::Data | Token::Let if depth == 0 => {
+                // the parser only looks ahead, braces of the item are still counted below
+                let mut parser = ItemParser {
+                    tokens: &tokens,
+                    stream,
+                    source,
+                    interner,
+                    pos: pos + 1,
+                };
+                if let Some((name, kind)) = parser.item(keyword) {
+                    items.push(Item {
+                        name,
+                        kind,
+                        docs: doc_text(&pending),
+                        line: token.line,
+                    });
+                }
+                pending.clear();
+            }
+            Token::LBrace => depth += 1,
+            Token::RBrace => depth = depth.saturating_sub(1),
+            _ => pending.clear(),
+        }
+        pos += 1;
+    }
+
+    ModuleDocs {
+        docs: doc_text(&module),
+        items,
+    }
+}
+
+/// Joins doc comment lines, dropping the single space usually written after `///`.
+fn doc_text(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+struct ItemParser<'t, 'a> {
+    // every token of `stream`, so a position here is an index there
+    tokens: &'t [&'a AnalyzedToken],
+    stream: &'t TokenStream,
+    source: &'t str,
+    interner: &'t Interner,
+    pos: usize,
+}
+
+impl ItemParser<'_, '_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn name(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Identifier(name)) => {
+                let name = self.interner.resolve(*name).to_string();
+                self.pos += 1;
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, expected: impl Fn(&Token) -> bool) -> bool {
+        let found = self.peek().is_some_and(expected);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn item(&mut self, keyword: &Token) -> Option<(String, ItemKind)> {
+        let name = self.name()?;
+        let kind = match keyword {
+            Token::Op => {
+                if !self.eat(|t| matches!(t, Token::LeftParen)) {
+                    return None;
+                }
+                let params = self.params(|t| matches!(t, Token::RightParen));
+                let returns = self.ty(|t| matches!(t, Token::LBrace | Token::Semicolon));
+                ItemKind::Op {
+                    params,
+                    returns: returns.filter(|ty| ty != "nret"),
+                }
+            }
+            Token::Data => {
+                if !self.eat(|t| matches!(t, Token::LBrace)) {
+                    return None;
+                }
+                ItemKind::Data {
+                    fields: self.params(|t| matches!(t, Token::RBrace)),
+                }
+            }
+            _ => {
+                let ty = if self.eat(|t| matches!(t, Token::Colon)) {
+                    self.ty(|t| matches!(t, Token::Equals | Token::Semicolon))
+                } else {
+                    None
+                };
+                ItemKind::Let { ty }
+            }
+        };
+        Some((name, kind))
+    }
+
+    /// `name: type` pairs separated by commas, up to and including `close`.
+    fn params(&mut self, close: fn(&Token) -> bool) -> Vec<Param> {
+        let mut params = Vec::new();
+        while let Some(token) = self.peek() {
+            if close(token) {
+                self.pos += 1;
+                break;
+            }
+            let Some(name) = self.name() else {
+                self.pos += 1;
+                continue;
+            };
+            let ty = if self.eat(|t| matches!(t, Token::Colon)) {
+                let interner = self.interner;
+                self.ty(|t| close(t) || is_comma(t, interner))
+            } else {
+                None
+            };
+            let interner = self.interner;
+            self.eat(|t| is_comma(t, interner));
+            params.push(Param {
+                name,
+                ty: ty.unwrap_or_default(),
+            });
+        }
+        params
+    }
+
+    /// The spelling of the type tokens before the first token matching `end`.
+    fn ty(&mut self, end: impl Fn(&Token) -> bool) -> Option<String> {
+        let mut ty = String::new();
+        while let Some(token) = self.peek()
+            && !end(token)
+            && !token.is_comment()
+        {
+            ty.push_str(&self.spelling(token));
+            self.pos += 1;
+        }
+        (!ty.is_empty()).then_some(ty)
+    }
+
+    /// The token at `pos` as written in the source: `str` stays `str`.
+    fn spelling(&self, token: &Token) -> String {
+        let written = self
+            .stream
+            .byte_range(self.pos)
+            .and_then(|bytes| self.source.get(bytes));
+        if let Some(text) = written {
+            return text.to_string();
+        }
+        match token {
+            Token::Identifier(name) => self.interner.resolve(*name).to_string(),
+            Token::Symbol(s) => self.interner.resolve(*s).to_string(),
+            token => token.spelling().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+fn is_comma(token: &Token, interner: &Interner) -> bool {
+    matches!(token, Token::Symbol(s) if interner.resolve(*s) == ",")
+}
+
+impl Item {
+    /// The item as declared, without its body: `op add(a: i32, b: i32) i32`.
+    pub fn signature(&self) -> String {
+        match &self.kind {
+            ItemKind::Op { params, returns } => format!(
+                "op {}({}) {}",
+                self.name,
+                params
+                    .iter()
+                    .map(|p| format!("{}: {}", p.name, p.ty))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                returns.as_deref().unwrap_or("nret")
+            ),
+            ItemKind::Data { .. } => format!("data {}", self.name),
+            ItemKind::Let { ty: Some(ty) } => format!("let {}: {}", self.name, ty),
+            ItemKind::Let { ty: None } => format!("let {}", self.name),
+        }
+    }
+}
+
+impl ModuleDocs {
+    fn section(&self, pick: fn(&ItemKind) -> bool) -> Vec<&Item> {
+        self.items.iter().filter(|item| pick(&item.kind)).collect()
+    }
+
+    fn sections(&self) -> [(&'static str, Vec<&Item>); 3] {
+        [
+            (
+                "Operations",
+                self.section(|k| matches!(k, ItemKind::Op { .. })),
+            ),
+            ("Data", self.section(|k| matches!(k, ItemKind::Data { .. }))),
+            (
+                "Variables",
+                self.section(|k| matches!(k, ItemKind::Let { .. })),
+            ),
+        ]
+    }
+
+    pub fn to_markdown(&self, title: &str) -> String {
+        let mut out = format!("# {title}\n\n");
+        if !self.docs.is_empty() {
+            out.push_str(&format!("{}\n\n", self.docs));
+        }
+        for (heading, items) in self.sections() {
+            if items.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("## {heading}\n\n"));
+            for item in items {
+                out.push_str(&format!(
+                    "### `{}`\n\n```\n{}\n```\n\n",
+                    item.name,
+                    item.signature()
+                ));
+                if !item.docs.is_empty() {
+                    out.push_str(&format!("{}\n\n", item.docs));
+                }
+                let (header, params) = match &item.kind {
+                    ItemKind::Op { params, .. } => ("Parameter", params),
+                    ItemKind::Data { fields } => ("Field", fields),
+                    ItemKind::Let { .. } => continue,
+                };
+                if !params.is_empty() {
+                    out.push_str(&format!("| {header} | Type |\n| --- | --- |\n"));
+                    for param in params {
+                        out.push_str(&format!("| `{}` | `{}` |\n", param.name, param.ty));
+                    }
+                    out.push('\n');
+                }
+                if let ItemKind::Op { returns, .. } = &item.kind {
+                    match returns {
+                        Some(ty) => out.push_str(&format!("**Returns:** `{ty}`\n\n")),
+                        None => out.push_str("**Returns:** no return\n\n"),
+                    }
+                }
+            }
+        }
+        format!("{}\n", out.trim_end())
+    }
+
+    pub fn to_html(&self, title: &str) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+            escape(title)
+        );
+        out.push_str(&paragraphs(&self.docs));
+        for (heading, items) in self.sections() {
+            if items.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("<h2>{heading}</h2>\n"));
+            for item in items {
+                out.push_str(&format!(
+                    "<h3 id=\"{0}\"><code>{0}</code></h3>\n<pre><code>{1}</code></pre>\n",
+                    escape(&item.name),
+                    escape(&item.signature())
+                ));
+                out.push_str(&paragraphs(&item.docs));
+                let (header, params) = match &item.kind {
+                    ItemKind::Op { params, .. } => ("Parameter", params),
+                    ItemKind::Data { fields } => ("Field", fields),
+                    ItemKind::Let { .. } => continue,
+                };
+                if !params.is_empty() {
+                    out.push_str(&format!(
+                        "<table>\n<tr><th>{header}</th><th>Type</th></tr>\n"
+                    ));
+                    for param in params {
+                        out.push_str(&format!(
+                            "<tr><td><code>{}</code></td><td><code>{}</code></td></tr>\n",
+                            escape(&param.name),
+                            escape(&param.ty)
+                        ));
+                    }
+                    out.push_str("</table>\n");
+                }
+                if let ItemKind::Op { returns, .. } = &item.kind {
+                    match returns {
+                        Some(ty) => out.push_str(&format!(
+                            "<p><strong>Returns:</strong> <code>{}</code></p>\n",
+                            escape(ty)
+                        )),
+                        None => out.push_str("<p><strong>Returns:</strong> no return</p>\n"),
+                    }
+                }
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Doc text as HTML paragraphs, split at blank lines.
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("<p>{}</p>\n", escape(p)))
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
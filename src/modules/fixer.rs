@@ -0,0 +1,112 @@
+use super::analyser::analyze_chars;
+use super::lints::lint;
+use super::source_map::{FileId, SourceFile, SourceMap};
+use super::tokenizer::{TokenizeOptions, tokenize_with};
+use crate::error_handling::{Applicability, Diagnostics, Error, Suggestion};
+
+/// Re-lexing stops after this many rounds even if fixes keep uncovering new ones.
+const MAX_ROUNDS: usize = 16;
+
+/// The outcome of `fix_source`: the fixed text and what is still reported for it.
+pub struct Fixed {
+    pub file: FileId,
+    pub applied: usize,
+    pub remaining: Vec<Error>,
+}
+
+/// Lexes and lints `text`, applies every machine-applicable suggestion, and repeats until no
+/// more can be applied. A lexing error stops the lexer, so fixing it can reveal the next one.
+///
+/// Every round is registered in `sources`; `Fixed::file` holds the final text. `levels`
+/// only supplies the lint levels, `Fixed::remaining` is what to report for this file.
+pub fn fix_source(
+    sources: &mut SourceMap,
+    name: &str,
+    text: String,
+    levels: &Diagnostics,
+) -> Fixed {
+    fix_source_with(sources, name, text, &TokenizeOptions::default(), levels)
+}
+
+/// `fix_source` for a language other than `.lx`, or other tokenizer options.
+pub fn fix_source_with(
+    sources: &mut SourceMap,
+    name: &str,
+    text: String,
+    options: &TokenizeOptions,
+    levels: &Diagnostics,
+) -> Fixed {
+    let mut file = sources.add(name, text);
+    let mut applied = 0;
+    // a sink of its own, so only the latest text of this file is fixed and reported
+    let mut sink = check(sources, file, options, levels);
+
+    for _ in 0..MAX_ROUNDS {
+        let fixes: Vec<Suggestion> = sink
+            .diagnostics()
+            .iter()
+            .flat_map(|diag| diag.suggestions())
+            .filter(|s| s.applicability == Applicability::MachineApplicable)
+            .cloned()
+            .collect();
+        let (fixed, count) = apply_suggestions(sources.get(file), &fixes);
+        if count == 0 {
+            break;
+        }
+        applied += count;
+        file = sources.add(name, fixed);
+        sink = check(sources, file, options, levels);
+    }
+
+    Fixed {
+        file,
+        applied,
+        remaining: sink.into_diagnostics(),
+    }
+}
+
+fn check(
+    sources: &SourceMap,
+    file: FileId,
+    options: &TokenizeOptions,
+    levels: &Diagnostics,
+) -> Diagnostics {
+    let mut sink = levels.fresh();
+    let source = sources.get(file);
+    let chars = analyze_chars(source.text());
+    match tokenize_with(&chars, file, options) {
+        Ok(tokens) => lint(file, source, &tokens, options.language(), &mut sink),
+        Err(err) => sink.push(err),
+    }
+    sink
+}
+
+/// Applies `suggestions` to the text of `source`, skipping any that overlap one applied
+/// before. Returns the new text and how many were applied.
+pub fn apply_suggestions(source: &SourceFile, suggestions: &[Suggestion]) -> (String, usize) {
+    let mut edits: Vec<(usize, usize, &str)> = suggestions
+        .iter()
+        .filter_map(|s| {
+            let start = source.offset(s.span.line, s.span.column)?;
+            let end = source.offset(s.span.end_line, s.span.end_column)?;
+            Some((start, end, s.replacement.as_str()))
+        })
+        .collect();
+    edits.sort_by_key(|(start, end, _)| (*start, *end));
+    edits.dedup();
+
+    let mut text = String::with_capacity(source.text().len());
+    let mut pos = 0;
+    let mut applied = 0;
+    for (start, end, replacement) in edits {
+        if start < pos {
+            continue;
+        }
+        text.push_str(&source.text()[pos..start]);
+        text.push_str(replacement);
+        pos = end;
+        applied += 1;
+    }
+    text.push_str(&source.text()[pos..]);
+    (text, applied)
+}
@@ -0,0 +1,374 @@
+use super::analyser::{AnalyzedChar, CharKind, analyze_chars};
+use super::interner::Symbol;
+use super::source_map::{FileId, SourceMap};
+use super::tokenizer::{TokenizeOptions, tokenize_with};
+use super::tokens::{AnalyzedToken, Token};
+use crate::error_handling::Result;
+
+const INDENT: &str = "    ";
+
+/// What goes between two consecutive tokens in the formatted output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gap {
+    Nothing,
+    Space,
+    Newline,
+    BlankLine,
+}
+
+/// Formats the source of `file` into its canonical layout.
+///
+/// The source has to lex cleanly; the first lexing error is returned unchanged.
+/// Formatting an already formatted source gives back the same text.
+pub fn format_source(sources: &SourceMap, file: FileId) -> Result<String> {
+    format_source_with(sources, file, &TokenizeOptions::default())
+}
+
+/// `format_source` for a language other than `.lx`; comments are always kept.
+pub fn format_source_with(
+    sources: &SourceMap,
+    file: FileId,
+    options: &TokenizeOptions,
+) -> Result<String> {
+    let source = sources.get(file).text();
+    let chars = analyze_chars(source);
+    // `else` and `if` stay apart so a comment between them stays where it was
+    let options = options.clone().with_comments(true).with_split_else_if(true);
+    let tokens = tokenize_with(&chars, file, &options)?;
+    let text = SourceText::new(&chars);
+
+    let mut printer = Printer {
+        comma: tokens.interner().get(","),
+        ..Printer::default()
+    };
+    let mut prev: Option<(&AnalyzedToken, usize)> = None;
+    let mut prev_unary = false;
+
+    let mut iter = tokens.iter();
+    while let Some(token) = iter.next() {
+        let mut spelling = text.spelling(token);
+        // an interpolated string is kept as written, embedded expressions included
+        let mut last = token;
+        if let Token::StringStart = token.token {
+            let mut depth = 1;
+            for inner in iter.by_ref() {
+                match inner.token {
+                    Token::StringStart => depth += 1,
+                    Token::StringEnd => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    spelling = text.between(token, inner);
+                    last = inner;
+                    break;
+                }
+            }
+        }
+        let gap = match prev {
+            None => Gap::Nothing,
+            Some((p, p_end_line)) => {
+                let blank_in_source = token.line > p_end_line + 1;
+                printer.gap_between(p, token, blank_in_source, prev_unary)
+            }
+        };
+        if let Token::RBrace = token.token {
+            printer.indent = printer.indent.saturating_sub(1);
+        }
+        printer.emit(gap, &spelling);
+        match token.token {
+            Token::LBrace => printer.indent += 1,
+            Token::LeftParen => printer.parens += 1,
+            Token::RightParen => printer.parens = printer.parens.saturating_sub(1),
+            Token::LBracket => printer.brackets += 1,
+            Token::RBracket => printer.brackets = printer.brackets.saturating_sub(1),
+            _ => {}
+        }
+        prev_unary = match token.token {
+            Token::Not => true,
+            Token::Minus => !prev.is_some_and(|(p, _)| is_operand(&p.token)),
+            _ => false,
+        };
+        let end_line = token.line + spelling.matches('\n').count();
+        prev = Some((last, end_line));
+    }
+    let mut formatted = printer.finish();
+    // the analyser skips a U+FEFF the source map left, which is the user's text, not a mark
+    if source.starts_with('\u{feff}') {
+        formatted.insert(0, '\u{feff}');
+    }
+    // keep Windows line endings in files that use them
+    Ok(if source.contains("\r\n") {
+        formatted.replace('\n', "\r\n")
+    } else {
+        formatted
+    })
+}
+
+/// Renders a unified diff between the original and the formatted source.
+pub fn render_diff(path: &str, original: &str, formatted: &str) -> String {
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = formatted.lines().collect();
+    let ops = diff_lines(&old, &new);
+
+    let mut out = format!("--- {path}\n+++ {path} (formatted)\n");
+    let context = 2;
+    let mut i = 0;
+    while i < ops.len() {
+        if let DiffOp::Equal(..) = ops[i] {
+            i += 1;
+            continue;
+        }
+        // grow the hunk until there are more than 2 * context equal lines in a row
+        let start = i.saturating_sub(context);
+        let mut end = i;
+        let mut equal_run = 0;
+        while end < ops.len() && equal_run <= 2 * context {
+            match ops[end] {
+                DiffOp::Equal(..) => equal_run += 1,
+                _ => equal_run = 0,
+            }
+            end += 1;
+        }
+        let end = end - equal_run.saturating_sub(context);
+
+        let (old_start, new_start) = ops[start].positions();
+        let old_len = ops[start..end]
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Insert(..)))
+            .count();
+        let new_len = ops[start..end]
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Delete(..)))
+            .count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_len,
+            new_start + 1,
+            new_len
+        ));
+        for op in &ops[start..end] {
+            match *op {
+                DiffOp::Equal(o, _) => out.push_str(&format!(" {}\n", old[o])),
+                DiffOp::Delete(o, _) => out.push_str(&format!("-{}\n", old[o])),
+                DiffOp::Insert(_, n) => out.push_str(&format!("+{}\n", new[n])),
+            }
+        }
+        i = end;
+    }
+    out
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    indent: usize,
+    parens: usize,
+    brackets: usize,
+    // the symbol of `,` in the file, if it has one
+    comma: Option<Symbol>,
+}
+
+impl Printer {
+    fn gap_between(
+        &self,
+        prev: &AnalyzedToken,
+        curr: &AnalyzedToken,
+        blank_in_source: bool,
+        prev_unary: bool,
+    ) -> Gap {
+        let line_break = if blank_in_source {
+            Gap::BlankLine
+        } else {
+            Gap::Newline
+        };
+        let top_level = self.indent == 0;
+
+        // comments keep their place: trailing ones stay on the line, the rest get their own
+        if curr.token.is_comment() {
+            return if curr.line == prev.line && !prev.token.is_comment() {
+                Gap::Space
+            } else {
+                line_break
+            };
+        }
+        if prev.token.is_comment() {
+            if top_level && matches!(curr.token, Token::Op) && blank_in_source {
+                return Gap::BlankLine;
+            }
+            return line_break;
+        }
+        if top_level && matches!(curr.token, Token::Op) {
+            return Gap::BlankLine;
+        }
+
+        match (&prev.token, &curr.token) {
+            (Token::LBrace, Token::RBrace) => Gap::Nothing,
+            (Token::LBrace, _) => Gap::Newline,
+            (_, Token::RBrace) => Gap::Newline,
+            (Token::RBrace, Token::Else) => Gap::Space,
+            (Token::RBrace, Token::Semicolon | Token::RightParen) => Gap::Nothing,
+            (Token::RBrace, c) if self.is_comma(c) => Gap::Nothing,
+            // a closed top-level block ends an item, so it is followed by a blank line
+            (Token::RBrace, _) if top_level => Gap::BlankLine,
+            (Token::RBrace, _) => line_break,
+            (Token::Semicolon, _) if self.parens == 0 => line_break,
+            (p, _) if self.is_comma(p) => {
+                if self.parens == 0 && self.brackets == 0 && !top_level {
+                    line_break
+                } else {
+                    Gap::Space
+                }
+            }
+            (_, c) if self.is_comma(c) => Gap::Nothing,
+            (_, Token::Semicolon | Token::Colon | Token::RightParen | Token::RBracket) => {
+                Gap::Nothing
+            }
+            (Token::LeftParen | Token::LBracket | Token::DoubleColon, _) => Gap::Nothing,
+            (_, Token::DoubleColon) => Gap::Nothing,
+            (Token::Identifier(_), Token::LeftParen) => Gap::Nothing,
+            (p, Token::LBracket) if is_operand(p) => Gap::Nothing,
+            _ if prev_unary => Gap::Nothing,
+            _ => Gap::Space,
+        }
+    }
+
+    fn is_comma(&self, token: &Token) -> bool {
+        matches!(token, Token::Symbol(s) if Some(*s) == self.comma)
+    }
+
+    fn emit(&mut self, gap: Gap, text: &str) {
+        match gap {
+            Gap::Nothing => {}
+            Gap::Space => self.out.push(' '),
+            Gap::Newline | Gap::BlankLine => {
+                self.trim_line_end();
+                self.out.push('\n');
+                if gap == Gap::BlankLine {
+                    self.out.push('\n');
+                }
+                self.out.push_str(&INDENT.repeat(self.indent));
+            }
+        }
+        self.out.push_str(text);
+    }
+
+    fn trim_line_end(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+    }
+
+    fn finish(mut self) -> String {
+        self.trim_line_end();
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+}
+
+/// Original source split into chars, addressable by the (line, column) of a token.
+struct SourceText {
+    chars: Vec<char>,
+    line_starts: Vec<usize>,
+}
+
+impl SourceText {
+    /// The text the tokens were lexed from, with every line break as a single '\n'.
+    fn new(analyzed: &[AnalyzedChar]) -> SourceText {
+        let mut chars = Vec::with_capacity(analyzed.len());
+        let mut line_starts = vec![0];
+        for c in analyzed {
+            match c.kind {
+                CharKind::Char(c) | CharKind::Whitespace(c) => chars.push(c),
+                CharKind::Newline => {
+                    chars.push('\n');
+                    line_starts.push(chars.len());
+                }
+            }
+        }
+        SourceText { chars, line_starts }
+    }
+
+    /// Source text from the start of `first` to the end of `last`.
+    fn between(&self, first: &AnalyzedToken, last: &AnalyzedToken) -> String {
+        let start = self.line_starts[first.line - 1] + first.column - 1;
+        let end = self.line_starts[last.line - 1] + last.column - 1 + last.length;
+        self.chars[start..end.min(self.chars.len())]
+            .iter()
+            .collect()
+    }
+
+    fn spelling(&self, token: &AnalyzedToken) -> String {
+        let start = self.line_starts[token.line - 1] + token.column - 1;
+        let end = (start + token.length).min(self.chars.len());
+        let text: String = self.chars[start..end].iter().collect();
+        match token.token {
+            Token::Comment(_) | Token::DocComment(_) | Token::ModuleDocComment(_) => {
+                text.trim_end().to_string()
+            }
+            _ => text,
+        }
+    }
+}
+
+fn is_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Identifier(_)
+            | Token::Number(_)
+            | Token::StringLiteral(_)
+            | Token::StringEnd
+            | Token::CharLiteral(_)
+            | Token::True
+            | Token::False
+            | Token::RightParen
+            | Token::RBracket
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize, usize),
+    Insert(usize, usize),
+}
+
+impl DiffOp {
+    fn positions(&self) -> (usize, usize) {
+        match *self {
+            DiffOp::Equal(o, n) | DiffOp::Delete(o, n) | DiffOp::Insert(o, n) => (o, n),
+        }
+    }
+}
+
+/// Line diff based on the longest common subsequence of both sides.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for o in (0..old.len()).rev() {
+        for n in (0..new.len()).rev() {
+            lcs[o][n] = if old[o] == new[n] {
+                lcs[o + 1][n + 1] + 1
+            } else {
+                lcs[o + 1][n].max(lcs[o][n + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut o, mut n) = (0, 0);
+    while o < old.len() || n < new.len() {
+        if o < old.len() && n < new.len() && old[o] == new[n] {
+            ops.push(DiffOp::Equal(o, n));
+            o += 1;
+            n += 1;
+        } else if o < old.len() && (n == new.len() || lcs[o + 1][n] >= lcs[o][n + 1]) {
+            ops.push(DiffOp::Delete(o, n));
+            o += 1;
+        } else {
+            ops.push(DiffOp::Insert(o, n));
+            n += 1;
+        }
+    }
+    ops
+}
@@ -0,0 +1,440 @@
+use serde_json::{Value, json};
+
+use super::language::LanguageSpec;
+use super::tokens::Token;
+
+/// Editor grammar formats `enoc grammar` writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrammarFormat {
+    TextMate,
+    Vim,
+    Sublime,
+}
+
+impl GrammarFormat {
+    pub const ALL: [GrammarFormat; 3] = [
+        GrammarFormat::TextMate,
+        GrammarFormat::Vim,
+        GrammarFormat::Sublime,
+    ];
+
+    pub fn from_name(name: &str) -> Option<GrammarFormat> {
+        match name {
+            "textmate" => Some(GrammarFormat::TextMate),
+            "vim" => Some(GrammarFormat::Vim),
+            "sublime" => Some(GrammarFormat::Sublime),
+            _ => None,
+        }
+    }
+
+    /// The file the grammar for language `name` is written to.
+    pub fn file_name(&self, name: &str) -> String {
+        match self {
+            GrammarFormat::TextMate => format!("{name}.tmLanguage.json"),
+            GrammarFormat::Vim => format!("{name}.vim"),
+            GrammarFormat::Sublime => format!("{name}.sublime-syntax"),
+        }
+    }
+
+    pub fn generate(&self, spec: &LanguageSpec, name: &str) -> String {
+        let grammar = Grammar::of(spec, name);
+        match self {
+            GrammarFormat::TextMate => format!("{:#}\n", grammar.textmate()),
+            GrammarFormat::Vim => grammar.vim(),
+            GrammarFormat::Sublime => grammar.sublime(),
+        }
+    }
+}
+
+/// How a keyword is highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Control,
+    Declaration,
+    Type,
+    Constant,
+    Operator,
+}
+
+impl Category {
+    const ALL: [Category; 5] = [
+        Category::Control,
+        Category::Declaration,
+        Category::Type,
+        Category::Constant,
+        Category::Operator,
+    ];
+
+    fn of(token: &Token) -> Category {
+        match token {
+            Token::Let | Token::Op | Token::Data => Category::Declaration,
+            Token::IntType(_)
+            | Token::FloatType(_)
+            | Token::String
+            | Token::Char
+            | Token::Bool
+            | Token::Nret => Category::Type,
+            Token::True | Token::False => Category::Constant,
+            Token::Symbol(_) | Token::And | Token::Or => Category::Operator,
+            _ => Category::Control,
+        }
+    }
+
+    fn scope(&self) -> &'static str {
+        match self {
+            Category::Control => "keyword.control",
+            Category::Declaration => "storage.type",
+            Category::Type => "support.type",
+            Category::Constant => "constant.language",
+            Category::Operator => "keyword.operator.word",
+        }
+    }
+
+    fn vim_group(&self) -> (&'static str, &'static str) {
+        match self {
+            Category::Control => ("Control", "Statement"),
+            Category::Declaration => ("Declaration", "Keyword"),
+            Category::Type => ("Type", "Type"),
+            Category::Constant => ("Boolean", "Boolean"),
+            Category::Operator => ("WordOperator", "Operator"),
+        }
+    }
+}
+
+/// The parts of a language every grammar format is built from.
+struct Grammar<'a> {
+    name: &'a str,
+    keywords: Vec<(Category, Vec<&'a str>)>,
+    // longest first, so `->` is tried before `-`
+    operators: Vec<&'a str>,
+    line_comment: Option<&'a str>,
+    docs: Vec<&'a str>,
+    quote: String,
+    char_quote: String,
+}
+
+impl<'a> Grammar<'a> {
+    fn of(spec: &'a LanguageSpec, name: &'a str) -> Grammar<'a> {
+        let keywords = Category::ALL
+            .into_iter()
+            .map(|category| {
+                let mut words: Vec<&str> = spec
+                    .keyword_tokens()
+                    .filter(|(_, token)| Category::of(token) == category)
+                    .map(|(word, _)| word)
+                    .collect();
+                words.sort();
+                (category, words)
+            })
+            .filter(|(_, words)| !words.is_empty())
+            .collect();
+        let mut operators: Vec<&str> = spec.operators().collect();
+        operators.sort_by(|a, b| b.chars().count().cmp(&a.chars().count()).then(a.cmp(b)));
+        Grammar {
+            name,
+            keywords,
+            operators,
+            line_comment: spec.line_comment(),
+            docs: [spec.doc_comment(), spec.module_doc_comment()]
+                .into_iter()
+                .flatten()
+                .collect(),
+            quote: spec.string_quote().to_string(),
+            char_quote: spec.char_quote().to_string(),
+        }
+    }
+
+    /// Oniguruma patterns for doc comments and then plain comments, with their scopes.
+    fn comments(&self) -> Vec<(String, String)> {
+        let doc = self.docs.iter().map(|marker| {
+            (
+                format!("{}(?!{}).*$", regex(marker), not_again(marker)),
+                format!("comment.line.documentation.{}", self.name),
+            )
+        });
+        let line = self.line_comment.map(|marker| {
+            (
+                format!("{}.*$", regex(marker)),
+                format!("comment.line.{}", self.name),
+            )
+        });
+        doc.chain(line).collect()
+    }
+
+    fn keyword_pattern(words: &[&str]) -> String {
+        let words: Vec<String> = words.iter().map(|w| regex(w)).collect();
+        format!("\\b(?:{})\\b", words.join("|"))
+    }
+
+    fn operator_pattern(&self) -> String {
+        let operators: Vec<String> = self.operators.iter().map(|o| regex(o)).collect();
+        operators.join("|")
+    }
+
+    fn textmate(&self) -> Value {
+        let name = self.name;
+        let quote = regex(&self.quote);
+        let comments: Vec<Value> = self
+            .comments()
+            .into_iter()
+            .map(|(pattern, scope)| json!({ "name": scope, "match": pattern }))
+            .collect();
+        let keywords: Vec<Value> = self
+            .keywords
+            .iter()
+            .map(|(category, words)| {
+                json!({
+                    "name": format!("{}.{name}", category.scope()),
+                    "match": Grammar::keyword_pattern(words),
+                })
+            })
+            .collect();
+        json!({
+            "name": name,
+            "scopeName": format!("source.{name}"),
+            "fileTypes": [name],
+            "patterns": [
+                { "include": "#comments" },
+                { "include": "#strings" },
+                { "include": "#chars" },
+                { "include": "#numbers" },
+                { "include": "#keywords" },
+                { "include": "#operators" },
+            ],
+            "repository": {
+                "comments": { "patterns": comments },
+                "strings": { "patterns": [
+                    {
+                        "name": format!("string.quoted.triple.{name}"),
+                        "begin": quote.repeat(3),
+                        "end": quote.repeat(3),
+                    },
+                    {
+                        "name": format!("string.quoted.raw.{name}"),
+                        "match": format!("r(#*){quote}.*?{quote}\\1"),
+                    },
+                    {
+                        "name": format!("string.quoted.double.{name}"),
+                        "begin": quote,
+                        "end": format!("{quote}|$"),
+                        "patterns": [
+                            {
+                                "name": format!("constant.character.escape.{name}"),
+                                "match": "\\{\\{|\\}\\}",
+                            },
+                            {
+                                "name": format!("meta.interpolation.{name}"),
+                                "begin": "\\{",
+                                "end": "\\}",
+                                "patterns": [{ "include": "$self" }],
+                            },
+                        ],
+                    },
+                ]},
+                "chars": {
+                    "name": format!("string.quoted.single.{name}"),
+                    "match": format!("{0}.{0}", regex(&self.char_quote)),
+                },
+                "numbers": {
+                    "name": format!("constant.numeric.{name}"),
+                    "match": "\\b\\d+\\b",
+                },
+                "keywords": { "patterns": keywords },
+                "operators": {
+                    "name": format!("keyword.operator.{name}"),
+                    "match": self.operator_pattern(),
+                },
+            },
+        })
+    }
+
+    fn sublime(&self) -> String {
+        let name = self.name;
+        let quote = regex(&self.quote);
+        let mut out = format!(
+            "%YAML 1.2\n---\n# Generated by `enoc grammar`, do not edit.\nname: {name}\nfile_extensions: [{name}]\nscope: source.{name}\n\ncontexts:\n  main:\n"
+        );
+        for (pattern, scope) in self.comments() {
+            out.push_str(&rule(&pattern, &scope));
+        }
+        out.push_str(&format!(
+            "    - match: {}\n      push: triple_string\n",
+            yaml(&quote.repeat(3))
+        ));
+        out.push_str(&rule(
+            &format!("r(#*){quote}.*?{quote}\\1"),
+            &format!("string.quoted.raw.{name}"),
+        ));
+        out.push_str(&format!(
+            "    - match: {}\n      push: string\n",
+            yaml(&quote)
+        ));
+        out.push_str(&rule(
+            &format!("{0}.{0}", regex(&self.char_quote)),
+            &format!("string.quoted.single.{name}"),
+        ));
+        out.push_str(&rule("\\b\\d+\\b", &format!("constant.numeric.{name}")));
+        for (category, words) in &self.keywords {
+            out.push_str(&rule(
+                &Grammar::keyword_pattern(words),
+                &format!("{}.{name}", category.scope()),
+            ));
+        }
+        out.push_str(&rule(
+            &self.operator_pattern(),
+            &format!("keyword.operator.{name}"),
+        ));
+        out.push_str(&format!(
+            "\n  string:\n    - meta_scope: string.quoted.double.{name}\n    - match: {}\n      pop: true\n    - match: '$'\n      pop: true\n",
+            yaml(&quote)
+        ));
+        out.push_str(&rule(
+            "\\{\\{|\\}\\}",
+            &format!("constant.character.escape.{name}"),
+        ));
+        out.push_str("    - match: '\\{'\n      push: interpolation\n");
+        out.push_str(&format!(
+            "\n  interpolation:\n    - clear_scopes: 1\n    - meta_scope: meta.interpolation.{name}\n    - match: '\\}}'\n      pop: true\n    - include: main\n"
+        ));
+        out.push_str(&format!(
+            "\n  triple_string:\n    - meta_scope: string.quoted.triple.{name}\n    - match: {}\n      pop: true\n",
+            yaml(&quote.repeat(3))
+        ));
+        out
+    }
+
+    fn vim(&self) -> String {
+        let name = self.name;
+        let quote = &self.quote;
+        let mut out = format!(
+            "\" Vim syntax file\n\" Language: {name}\n\" Generated by `enoc grammar`, do not edit.\n\nif exists(\"b:current_syntax\")\n  finish\nendif\n\n"
+        );
+        let mut links = Vec::new();
+
+        for (category, words) in &self.keywords {
+            let (group, link) = category.vim_group();
+            out.push_str(&format!("syn keyword {name}{group} {}\n", words.join(" ")));
+            links.push((group, link));
+        }
+        out.push_str(&format!("syn match {name}Number \"\\<\\d\\+\\>\"\n"));
+        links.push(("Number", "Number"));
+        if !self.operators.is_empty() {
+            let operators: Vec<String> = self.operators.iter().map(|o| vim_literal(o)).collect();
+            out.push_str(&format!(
+                "syn match {name}Operator {}\n",
+                vim_pattern(&operators.join("\\|"))
+            ));
+            links.push(("Operator", "Operator"));
+        }
+        let q = vim_literal(quote);
+        out.push_str(&format!(
+            "syn match {name}Char {}\n",
+            vim_pattern(&format!("{0}\\.{0}", vim_literal(&self.char_quote)))
+        ));
+        out.push_str(&format!(
+            "syn match {name}BraceEscape {} contained\n",
+            vim_pattern("{{\\|}}")
+        ));
+        out.push_str(&format!(
+            "syn region {name}Interpolation matchgroup={name}InterpolationDelimiter start={} end={} contained contains=TOP\n",
+            vim_pattern("{"),
+            vim_pattern("}")
+        ));
+        out.push_str(&format!(
+            "syn region {name}String start={} end={} oneline contains={name}BraceEscape,{name}Interpolation\n",
+            vim_pattern(&q),
+            vim_pattern(&format!("{q}\\|\\$"))
+        ));
+        out.push_str(&format!(
+            "syn region {name}RawString start={} end={} oneline\n",
+            vim_pattern(&format!("r\\z(#\\*\\){q}")),
+            vim_pattern(&format!("{q}\\z1"))
+        ));
+        out.push_str(&format!(
+            "syn region {name}TripleString start={} end={}\n",
+            vim_pattern(&q.repeat(3)),
+            vim_pattern(&q.repeat(3))
+        ));
+        links.extend([
+            ("Char", "Character"),
+            ("BraceEscape", "SpecialChar"),
+            ("InterpolationDelimiter", "Special"),
+            ("String", "String"),
+            ("RawString", "String"),
+            ("TripleString", "String"),
+        ]);
+        if let Some(marker) = self.line_comment {
+            out.push_str(&format!(
+                "syn match {name}Comment {}\n",
+                vim_pattern(&format!("{}\\.\\*\\$", vim_literal(marker)))
+            ));
+            links.push(("Comment", "Comment"));
+        }
+        for marker in &self.docs {
+            let again = marker.chars().last().map(|c| vim_literal(&c.to_string()));
+            out.push_str(&format!(
+                "syn match {name}DocComment {}\n",
+                vim_pattern(&format!(
+                    "{}\\({}\\)\\@!\\.\\*\\$",
+                    vim_literal(marker),
+                    again.unwrap_or_default()
+                ))
+            ));
+        }
+        if !self.docs.is_empty() {
+            links.push(("DocComment", "SpecialComment"));
+        }
+
+        out.push('\n');
+        for (group, link) in links {
+            out.push_str(&format!("hi def link {name}{group} {link}\n"));
+        }
+        out.push_str(&format!("\nlet b:current_syntax = \"{name}\"\n"));
+        out
+    }
+}
+
+fn rule(pattern: &str, scope: &str) -> String {
+    format!("    - match: {}\n      scope: {scope}\n", yaml(pattern))
+}
+
+/// `text` as a literal in an Oniguruma pattern.
+fn regex(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_punctuation() {
+                format!("\\{c}")
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+/// The last char of a doc comment marker, which must not follow it: `////` is no doc comment.
+fn not_again(marker: &str) -> String {
+    marker
+        .chars()
+        .last()
+        .map(|c| regex(&c.to_string()))
+        .unwrap_or_default()
+}
+
+/// A single-quoted YAML scalar.
+fn yaml(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// `text` as a literal in a very nomagic (`\V`) Vim pattern.
+fn vim_literal(text: &str) -> String {
+    text.replace('\\', "\\\\")
+}
+
+/// A `\V` pattern between delimiters that do not occur in it.
+fn vim_pattern(pattern: &str) -> String {
+    let delimiter = ['"', '+', '@', '#', '%', '!', '~', ';']
+        .into_iter()
+        .find(|c| !pattern.contains(*c))
+        .unwrap_or('"');
+    format!("{delimiter}\\V{pattern}{delimiter}")
+}
@@ -0,0 +1,249 @@
+use std::ops::Range;
+
+use super::analyser::analyze_chars;
+use super::position::Position;
+use super::source_map::FileId;
+use super::token_stream::{Extent, TokenStream};
+use super::tokenizer::{TokenizeOptions, tokenize_in, tokenize_with};
+use super::tokens::{AnalyzedToken, Token};
+use crate::error_handling::Result;
+
+/// Replaces the bytes `range` of a source with `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, text: &str) -> TextEdit {
+        TextEdit {
+            range,
+            text: text.to_string(),
+        }
+    }
+
+    /// `source` with the edit applied. Panics if `range` is not on char boundaries of
+    /// `source`.
+    pub fn apply(&self, source: &str) -> String {
+        let mut edited = source.to_string();
+        edited.replace_range(self.range.clone(), &self.text);
+        edited
+    }
+}
+
+/// The tokens of an edited source, and which of them the edit changed.
+pub struct Relexed {
+    pub tokens: TokenStream,
+    /// Indices in `tokens` of the tokens that are new or different. The tokens before
+    /// them are unchanged and the ones after them only moved; after lexing the whole
+    /// file again, every index is in the range.
+    pub changed: Range<usize>,
+}
+
+/// Lexes `old_source` with `edit` applied, reusing the tokens of `previous` outside the
+/// region the edit can affect.
+///
+/// `previous` has to be what `tokenize_with` made of `old_source` with the same
+/// `options`. The region starts at a token that follows whitespace outside any string,
+/// and ends before the first such token after the edit; that token and all after it are
+/// only moved. When the region does not lex on its own, for example because the edit
+/// opened a string that closes further down, the whole file is lexed again. Errors are
+/// those of lexing the whole edited source.
+pub fn relex(
+    previous: &TokenStream,
+    old_source: &str,
+    edit: &TextEdit,
+    file: FileId,
+    options: &TokenizeOptions,
+) -> Result<Relexed> {
+    let new_source = edit.apply(old_source);
+    let old = previous.as_slice();
+    let extents = previous.extents();
+    let bytes: Option<Vec<Range<usize>>> = extents.iter().map(|e| e.bytes.clone()).collect();
+    let Some(bytes) = bytes else {
+        return relex_all(&new_source, file, options);
+    };
+    // byte offsets of the tokens are counted after a byte order mark
+    if old.is_empty() || old_source.starts_with('\u{feff}') || new_source.starts_with('\u{feff}') {
+        return relex_all(&new_source, file, options);
+    }
+
+    // how many interpolated strings are open before each token
+    let mut depth = Vec::with_capacity(old.len());
+    let mut open = 0usize;
+    for token in old {
+        depth.push(open);
+        match token.token {
+            Token::StringStart => open += 1,
+            Token::StringEnd => open = open.saturating_sub(1),
+            _ => {}
+        }
+    }
+    let space_before = |offset: usize| {
+        old_source[..offset]
+            .chars()
+            .next_back()
+            .filter(|c| c.is_whitespace())
+            .map(|c| offset - c.len_utf8())
+    };
+    // an `if` after it would turn an `else` into an `ElseIf`, and the comments between
+    // the two lie inside that `ElseIf`
+    let after_else = |i: usize| {
+        (0..i)
+            .rev()
+            .find(|&j| !old[j].token.is_comment())
+            .is_some_and(|j| matches!(old[j].token, Token::Else) || bytes[j].end > bytes[i].start)
+    };
+
+    let edit_start = edit.range.start;
+    let edit_end = edit.range.end;
+    let before_edit = bytes.partition_point(|b| b.start <= edit_start);
+    let restart = (0..before_edit).rev().find(|&i| {
+        depth[i] == 0
+            && (bytes[i].start == 0 || space_before(bytes[i].start).is_some())
+            && !after_else(i)
+    });
+    let (first, restart_byte, restart_at) = match restart {
+        Some(i) => (i, bytes[i].start, Position::new(old[i].line, old[i].column)),
+        None => (0, 0, Position::new(1, 1)),
+    };
+    let resync = (bytes.partition_point(|b| b.start < edit_end)..old.len()).find(|&j| {
+        depth[j] == 0
+            && space_before(bytes[j].start).is_some_and(|space| space >= edit_end)
+            && !matches!(old[j].token, Token::If)
+            && !old[j].token.is_comment()
+    });
+    let (last, resync_byte) = match resync {
+        Some(j) => (j, bytes[j].start),
+        None => (old.len(), old_source.len()),
+    };
+    let resync_new = resync_byte + edit.text.len() - (edit_end - edit_start);
+
+    let region = &new_source[restart_byte..resync_new];
+    // comments are kept to see where they end, and dropped below if they are not wanted
+    let with_comments = options.clone().with_comments(true);
+    // names new to the file are added after the old ones, whose symbols stay the same
+    let interner = previous.interner().clone();
+    let Ok(mut lexed) = tokenize_in(&analyze_chars(region), file, &with_comments, interner) else {
+        return relex_all(&new_source, file, options);
+    };
+    // a comment at the end of the region would have gone on past it
+    if resync.is_some()
+        && lexed
+            .extents()
+            .iter()
+            .any(|e| e.bytes.as_ref().is_some_and(|b| b.end >= region.len()))
+    {
+        return relex_all(&new_source, file, options);
+    }
+
+    // region positions count from its start, which is `restart_at` in the file
+    let place = |at: Position| match at.line {
+        1 => Position::new(restart_at.line, restart_at.column + at.column - 1),
+        line => Position::new(restart_at.line + line - 1, at.column),
+    };
+    let resync_at = place(Position::new(lexed.eof().line, lexed.eof().column));
+    // tokens after the region move like the token the region ends at
+    let old_resync_at = old
+        .get(last)
+        .map_or(resync_at, |t| Position::new(t.line, t.column));
+    // `None` for positions in front of the old region end that moved some other way
+    let shift = |at: Position| {
+        let column = match at.line == old_resync_at.line {
+            true => (at.column + resync_at.column).checked_sub(old_resync_at.column)?,
+            false => at.column,
+        };
+        let line = (at.line + resync_at.line).checked_sub(old_resync_at.line)?;
+        Some(Position::new(line, column))
+    };
+    let after_region = |at: Position| shift(at).unwrap_or(at);
+    let shift_bytes = |b: &Range<usize>| {
+        let start = (b.start + resync_new).checked_sub(resync_byte)?;
+        Some(start..b.end + resync_new - resync_byte)
+    };
+
+    let interner = lexed.take_interner();
+    let mut tokens: Vec<AnalyzedToken> = old[..first].to_vec();
+    let mut new_extents: Vec<Extent> = extents[..first].to_vec();
+    for (token, extent) in lexed.as_slice().iter().zip(lexed.extents()) {
+        if !options.keep_comments() && matches!(token.token, Token::Comment(_)) {
+            continue;
+        }
+        tokens.push(moved(token, place));
+        new_extents.push(Extent {
+            end: place(extent.end),
+            bytes: extent
+                .bytes
+                .as_ref()
+                .map(|b| b.start + restart_byte..b.end + restart_byte),
+        });
+    }
+    let relexed = first..tokens.len();
+    for (token, extent) in old[last..].iter().zip(&extents[last..]) {
+        tokens.push(moved(token, after_region));
+        new_extents.push(Extent {
+            end: after_region(extent.end),
+            bytes: extent.bytes.as_ref().and_then(shift_bytes),
+        });
+    }
+    let eof = match resync {
+        Some(_) => moved(previous.eof(), after_region),
+        None => moved(lexed.eof(), place),
+    };
+
+    // the region often starts and ends with tokens the edit did not touch
+    let new = || {
+        tokens[relexed.clone()]
+            .iter()
+            .zip(&new_extents[relexed.clone()])
+    };
+    let same_start = new()
+        .zip(old[first..last].iter().zip(&bytes[first..last]))
+        .take_while(|((a, a_extent), (b, b_bytes))| {
+            same(a, b) && a_extent.bytes.as_ref() == Some(b_bytes)
+        })
+        .count();
+    let same_end = new()
+        .skip(same_start)
+        .rev()
+        .zip(
+            old[first + same_start..last]
+                .iter()
+                .zip(&bytes[first + same_start..last])
+                .rev(),
+        )
+        .take_while(|((a, a_extent), (b, b_bytes))| {
+            shift(Position::new(b.line, b.column)).is_some()
+                && same(a, &moved(b, after_region))
+                && a_extent.bytes == shift_bytes(b_bytes)
+        })
+        .count();
+    let changed = relexed.start + same_start..relexed.end - same_end;
+
+    Ok(Relexed {
+        tokens: TokenStream::from_parts(tokens, new_extents, eof, Some(file), interner),
+        changed,
+    })
+}
+
+fn relex_all(source: &str, file: FileId, options: &TokenizeOptions) -> Result<Relexed> {
+    let tokens = tokenize_with(&analyze_chars(source), file, options)?;
+    let changed = 0..tokens.len();
+    Ok(Relexed { tokens, changed })
+}
+
+fn moved(token: &AnalyzedToken, to: impl Fn(Position) -> Position) -> AnalyzedToken {
+    let at = to(Position::new(token.line, token.column));
+    AnalyzedToken {
+        line: at.line,
+        column: at.column,
+        ..token.clone()
+    }
+}
+
+fn same(a: &AnalyzedToken, b: &AnalyzedToken) -> bool {
+    a.token == b.token
+        && (a.line, a.column, a.length) == (b.line, b.column, b.length)
+        && a.soft_keyword == b.soft_keyword
+}
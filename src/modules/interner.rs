@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::tokens::{AnalyzedToken, Token};
+
+/// Handle to a name in an `Interner`. Two symbols from the same interner are equal
+/// exactly when their texts are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Stores every distinct identifier and operator text of a lexing session once, and hands
+/// out a `Symbol` for it.
+///
+/// A `LanguageSpec` interns the operators of its tables; every `tokenize_with` starts
+/// from a copy of those, so the symbols of the language are the same in all its streams.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    symbols: HashMap<Arc<str>, Symbol>,
+    texts: Vec<Arc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+    /// The symbol for `text`, adding it if it is new.
+    pub fn intern(&mut self, text: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(text) {
+            return *symbol;
+        }
+        let symbol = Symbol(self.texts.len() as u32);
+        let text: Arc<str> = Arc::from(text);
+        self.texts.push(text.clone());
+        self.symbols.insert(text, symbol);
+        symbol
+    }
+    /// The symbol for `text`, if it was interned.
+    pub fn get(&self, text: &str) -> Option<Symbol> {
+        self.symbols.get(text).copied()
+    }
+    /// The text of `symbol`. Panics if `symbol` is from another interner with more names.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.texts[symbol.0 as usize]
+    }
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+    /// Shows `value` like its `Debug` output, with the text of every symbol in place of
+    /// its number: `Identifier("x")` rather than `Identifier(Symbol(7))`. A `Token` also
+    /// displays as an error message names it.
+    pub fn resolved<'a, T>(&'a self, value: &'a T) -> Resolved<'a, T> {
+        Resolved {
+            value,
+            interner: self,
+        }
+    }
+}
+
+/// A `Token` or `AnalyzedToken` with its symbols resolved, from `Interner::resolved`.
+pub struct Resolved<'a, T> {
+    value: &'a T,
+    interner: &'a Interner,
+}
+
+impl fmt::Debug for Resolved<'_, Token> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Token::Identifier(name) => write!(f, "Identifier({:?})", self.interner.resolve(*name)),
+            Token::Symbol(text) => write!(f, "Symbol({:?})", self.interner.resolve(*text)),
+            token => write!(f, "{token:?}"),
+        }
+    }
+}
+
+/// The token the way an error message names it: `` `let` ``, `` identifier `x` ``.
+impl fmt::Display for Resolved<'_, Token> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(spelling) = self.value.spelling() {
+            return write!(f, "`{spelling}`");
+        }
+        match self.value {
+            Token::Identifier(name) => write!(f, "identifier `{}`", self.interner.resolve(*name)),
+            Token::Symbol(text) => write!(f, "`{}`", self.interner.resolve(*text)),
+            Token::Number(n) => write!(f, "number `{n}`"),
+            Token::StringLiteral(text) => write!(f, "string {text:?}"),
+            Token::StringFragment(text) => write!(f, "string text {text:?}"),
+            Token::CharLiteral(c) => write!(f, "char {c:?}"),
+            token => write!(f, "{}", token.kind()),
+        }
+    }
+}
+
+impl fmt::Debug for Resolved<'_, AnalyzedToken> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let token = self.value;
+        write!(
+            f,
+            "AnalyzedToken -> \x1b[033;1m{:?}\x1b[0m [line: {}, column: {}, length: {}]",
+            self.interner.resolved(&token.token),
+            token.line,
+            token.column,
+            token.length
+        )?;
+        if let Some(keyword) = &token.soft_keyword {
+            write!(f, " (could be {:?})", self.interner.resolved(keyword))?;
+        }
+        Ok(())
+    }
+}
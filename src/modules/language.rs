@@ -0,0 +1,534 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use once_cell::sync::Lazy;
+use toml::{Table, Value};
+use unicode_xid::UnicodeXID;
+
+use super::interner::Interner;
+use super::tokens::{FloatType, IntType, Token};
+use crate::error_handling::{Error, ErrorCode, ErrorType, Result};
+
+/// The built-in `.lx` language.
+pub static LX: Lazy<LanguageSpec> = Lazy::new(LanguageSpec::lx);
+
+/// Which chars make up an identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentifierRules {
+    /// UAX #31 (XID_Start, then XID_Continue) when set, ASCII letters and digits otherwise.
+    pub unicode: bool,
+    /// Chars allowed anywhere in an identifier on top of the letters, e.g. `_`.
+    pub start: Vec<char>,
+    /// Chars allowed after the first one on top of letters, digits and `start`.
+    pub continues: Vec<char>,
+}
+
+impl IdentifierRules {
+    pub fn is_start(&self, c: char) -> bool {
+        self.start.contains(&c)
+            || if self.unicode {
+                c.is_xid_start()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+    }
+
+    pub fn is_continue(&self, c: char) -> bool {
+        self.is_start(c)
+            || self.continues.contains(&c)
+            || if self.unicode {
+                c.is_xid_continue()
+            } else {
+                c.is_ascii_alphanumeric()
+            }
+    }
+}
+
+/// Everything the tokenizer needs to know about the language it lexes.
+///
+/// `LanguageSpec::default()` is the `.lx` language; `load` reads a dialect from TOML.
+#[derive(Debug, Clone)]
+pub struct LanguageSpec {
+    keywords: HashMap<String, Token>,
+    // keywords only where the parser expects one, lexed as identifiers everywhere
+    contextual_keywords: HashMap<String, Token>,
+    operators: HashMap<String, Token>,
+    line_comment: Option<String>,
+    doc_comment: Option<String>,
+    module_doc_comment: Option<String>,
+    string_quote: char,
+    char_quote: char,
+    identifiers: IdentifierRules,
+    // the texts of the `Symbol` tokens in the tables, which every lexed file starts with
+    symbols: Interner,
+    // first chars of operators and comment markers, and the longest of them in chars
+    marker_starts: HashSet<char>,
+    longest_marker: usize,
+}
+
+impl Default for LanguageSpec {
+    fn default() -> Self {
+        LX.clone()
+    }
+}
+
+impl LanguageSpec {
+    fn lx() -> LanguageSpec {
+        let keywords = [
+            ("let", "Let"),
+            ("op", "Op"),
+            ("if", "If"),
+            ("else", "Else"),
+            ("while", "While"),
+            ("for", "For"),
+            ("loop", "Loop"),
+            ("success", "Success"),
+            ("i8", "IntType"),
+            ("i16", "IntType"),
+            ("i32", "IntType"),
+            ("i64", "IntType"),
+            ("i128", "IntType"),
+            ("isize", "IntType"),
+            ("u8", "IntType"),
+            ("u16", "IntType"),
+            ("u32", "IntType"),
+            ("u64", "IntType"),
+            ("u128", "IntType"),
+            ("usize", "IntType"),
+            ("string", "String"),
+            ("str", "String"),
+            ("char", "Char"),
+            ("f32", "FloatType"),
+            ("f64", "FloatType"),
+            ("bool", "Bool"),
+            ("break", "Break"),
+            ("continue", "Continue"),
+            ("true", "True"),
+            ("false", "False"),
+            ("nret", "Nret"),
+            ("and", "Symbol(&&)"),
+            ("or", "Symbol(||)"),
+        ];
+        let contextual_keywords = [("data", "Data")];
+        let operators = [
+            ("=", "Equals"),
+            (":", "Colon"),
+            (";", "Semicolon"),
+            ("::", "DoubleColon"),
+            (")", "RightParen"),
+            ("(", "LeftParen"),
+            ("{", "LBrace"),
+            ("}", "RBrace"),
+            ("-", "Minus"),
+            ("+", "Plus"),
+            ("/", "Slash"),
+            ("*", "Star"),
+            ("->", "Arrow"),
+            ("?", "Question"),
+            ("-<", "ChainBack"),
+            ("<-", "ReverseArrow"),
+            ("-!", "ChainNot"),
+            (">", "Symbol"),
+            ("%", "Percent"),
+            ("[", "LBracket"),
+            ("]", "RBracket"),
+            ("^", "Power"),
+            ("/*", "Symbol"),
+            ("==", "Equals"),
+            ("<=", "LessEqual"),
+            (">=", "GreaterEqual"),
+            ("<", "Symbol"),
+            (",", "Symbol"),
+        ];
+        let mut symbols = Interner::new();
+        let mut kinds = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(spelling, kind)| {
+                    let token = token_kind(kind, spelling, &mut symbols)
+                        .expect("built-in token kinds are valid");
+                    (spelling.to_string(), token)
+                })
+                .collect()
+        };
+        LanguageSpec {
+            keywords: kinds(&keywords),
+            contextual_keywords: kinds(&contextual_keywords),
+            operators: kinds(&operators),
+            line_comment: Some("//".to_string()),
+            doc_comment: Some("///".to_string()),
+            module_doc_comment: Some("//!".to_string()),
+            string_quote: '"',
+            char_quote: '\'',
+            identifiers: IdentifierRules {
+                unicode: true,
+                start: vec!['_'],
+                continues: Vec::new(),
+            },
+            symbols,
+            marker_starts: HashSet::new(),
+            longest_marker: 0,
+        }
+        .with_markers()
+    }
+
+    /// Reads a language definition from a TOML file. See `from_toml` for the format.
+    pub fn load(path: &str) -> Result<LanguageSpec> {
+        let text = fs::read_to_string(path).map_err(|e| Error::io(e, path))?;
+        LanguageSpec::from_toml(&text).map_err(|err| err.with_path(path))
+    }
+
+    /// A language from TOML with the optional tables `keywords`, `contextual_keywords`,
+    /// `operators`, `comments` (`line`, `doc`, `module_doc`), `strings` (`quote`,
+    /// `char_quote`) and `identifiers` (`unicode`, `start`, `continue`). A missing table
+    /// keeps the `.lx` one, except that `keywords` alone leaves no contextual keywords.
+    pub fn from_toml(text: &str) -> Result<LanguageSpec> {
+        let table: Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| invalid(e.message()))?;
+        let mut spec = LX.clone();
+
+        for (name, value) in &table {
+            let Value::Table(section) = value else {
+                return Err(invalid(&format!("'{name}' has to be a table")));
+            };
+            match name.as_str() {
+                "keywords" => spec.keywords = kinds(name, section, &mut spec.symbols)?,
+                "contextual_keywords" => {
+                    spec.contextual_keywords = kinds(name, section, &mut spec.symbols)?
+                }
+                "operators" => spec.operators = kinds(name, section, &mut spec.symbols)?,
+                "comments" => {
+                    spec.line_comment = text_of(section, "line")?;
+                    spec.doc_comment = text_of(section, "doc")?;
+                    spec.module_doc_comment = text_of(section, "module_doc")?;
+                }
+                "strings" => {
+                    if let Some(quote) = char_of(section, "quote")? {
+                        spec.string_quote = quote;
+                    }
+                    if let Some(quote) = char_of(section, "char_quote")? {
+                        spec.char_quote = quote;
+                    }
+                }
+                "identifiers" => {
+                    if let Some(unicode) = section.get("unicode") {
+                        spec.identifiers.unicode = unicode
+                            .as_bool()
+                            .ok_or_else(|| invalid("'identifiers.unicode' has to be a boolean"))?;
+                    }
+                    if let Some(start) = text_of(section, "start")? {
+                        spec.identifiers.start = start.chars().collect();
+                    }
+                    if let Some(continues) = text_of(section, "continue")? {
+                        spec.identifiers.continues = continues.chars().collect();
+                    }
+                }
+                _ => return Err(invalid(&format!("unknown table '{name}'"))),
+            }
+        }
+        // own keywords replace all of `.lx`'s, and a word is either reserved or contextual
+        match (
+            table.contains_key("keywords"),
+            table.contains_key("contextual_keywords"),
+        ) {
+            (true, true) => {
+                if let Some(word) = spec
+                    .contextual_keywords
+                    .keys()
+                    .find(|w| spec.keywords.contains_key(*w))
+                {
+                    return Err(invalid(&format!(
+                        "'{word}' is both a keyword and a contextual keyword"
+                    )));
+                }
+            }
+            (true, false) => spec.contextual_keywords.clear(),
+            (false, true) => {
+                let contextual = &spec.contextual_keywords;
+                spec.keywords
+                    .retain(|word, _| !contextual.contains_key(word));
+            }
+            (false, false) => {}
+        }
+        spec.check_quotes()?;
+        Ok(spec.with_markers())
+    }
+
+    /// The tokenizer tries strings first, so a quote shared with chars, a comment or an
+    /// operator would hide them.
+    fn check_quotes(&self) -> Result<()> {
+        if self.string_quote == self.char_quote {
+            return Err(invalid(&format!(
+                "'strings.quote' and 'strings.char_quote' are both `{}`",
+                self.string_quote
+            )));
+        }
+        let markers = [
+            ("comments.line", &self.line_comment),
+            ("comments.doc", &self.doc_comment),
+            ("comments.module_doc", &self.module_doc_comment),
+        ]
+        .into_iter()
+        .filter_map(|(key, marker)| Some((key.to_string(), marker.as_deref()?)))
+        .chain(
+            self.operators
+                .keys()
+                .map(|op| (format!("operators.{op}"), op.as_str())),
+        );
+        for (key, marker) in markers {
+            for (quote_key, quote) in [
+                ("strings.quote", self.string_quote),
+                ("strings.char_quote", self.char_quote),
+            ] {
+                if marker.starts_with(quote) {
+                    return Err(invalid(&format!(
+                        "'{quote_key}' and '{key}' both start with `{quote}`"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn with_markers(mut self) -> Self {
+        let markers: Vec<&String> = self
+            .operators
+            .keys()
+            .chain(&self.line_comment)
+            .chain(&self.doc_comment)
+            .chain(&self.module_doc_comment)
+            .collect();
+        self.marker_starts = markers.iter().filter_map(|m| m.chars().next()).collect();
+        self.longest_marker = markers.iter().map(|m| m.chars().count()).max().unwrap_or(0);
+        self
+    }
+
+    /// The token a word is lexed into if it is a reserved keyword.
+    pub fn keyword(&self, word: &str) -> Option<Token> {
+        self.keywords.get(word).cloned()
+    }
+
+    /// The keyword a word stands for where the parser expects one, if it is a contextual
+    /// keyword. Everywhere else it is an ordinary identifier.
+    pub fn contextual_keyword(&self, word: &str) -> Option<Token> {
+        self.contextual_keywords.get(word).cloned()
+    }
+
+    /// Every keyword, contextual ones included.
+    pub fn keywords(&self) -> impl Iterator<Item = &str> {
+        self.keyword_tokens().map(|(k, _)| k)
+    }
+
+    /// Every keyword, contextual ones included, with the token it stands for.
+    pub fn keyword_tokens(&self) -> impl Iterator<Item = (&str, &Token)> {
+        self.keywords
+            .iter()
+            .chain(&self.contextual_keywords)
+            .map(|(k, t)| (k.as_str(), t))
+    }
+
+    pub fn operators(&self) -> impl Iterator<Item = &str> {
+        self.operators.keys().map(String::as_str)
+    }
+
+    /// The longest operator `text` starts with, and its token.
+    pub fn operator_at(&self, text: &str) -> Option<(&str, Token)> {
+        text.char_indices()
+            .map(|(i, c)| &text[..i + c.len_utf8()])
+            .rev()
+            .find_map(|prefix| {
+                self.operators
+                    .get_key_value(prefix)
+                    .map(|(spelling, token)| (spelling.as_str(), token.clone()))
+            })
+    }
+
+    /// Whether an operator or comment can start with `c`.
+    pub fn is_marker_start(&self, c: char) -> bool {
+        self.marker_starts.contains(&c)
+    }
+
+    /// How many chars to look at to match the longest operator or comment marker.
+    pub fn longest_marker(&self) -> usize {
+        self.longest_marker
+    }
+
+    pub fn line_comment(&self) -> Option<&str> {
+        self.line_comment.as_deref()
+    }
+
+    pub fn doc_comment(&self) -> Option<&str> {
+        self.doc_comment.as_deref()
+    }
+
+    pub fn module_doc_comment(&self) -> Option<&str> {
+        self.module_doc_comment.as_deref()
+    }
+
+    /// Whether `text` starts with a comment or doc comment marker.
+    pub fn is_comment(&self, text: &str) -> bool {
+        [
+            &self.line_comment,
+            &self.doc_comment,
+            &self.module_doc_comment,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|marker| text.starts_with(marker.as_str()))
+    }
+
+    /// The kind of comment `text`, which starts with a comment marker, is: its token with
+    /// the text after the marker. `///` documents an item and `//!` the file, but
+    /// `////` is an ordinary comment again.
+    pub fn comment(&self, text: &str) -> Token {
+        let doc = |marker: &Option<String>| {
+            let marker = marker.as_deref()?;
+            let rest = text.strip_prefix(marker)?;
+            let last = marker.chars().last()?;
+            (!rest.starts_with(last)).then(|| rest.to_string())
+        };
+        if let Some(rest) = doc(&self.doc_comment) {
+            Token::DocComment(rest)
+        } else if let Some(rest) = doc(&self.module_doc_comment) {
+            Token::ModuleDocComment(rest)
+        } else {
+            let marker = self.line_comment.as_deref().unwrap_or("");
+            Token::Comment(text.strip_prefix(marker).unwrap_or(text).to_string())
+        }
+    }
+
+    pub fn string_quote(&self) -> char {
+        self.string_quote
+    }
+
+    pub fn char_quote(&self) -> char {
+        self.char_quote
+    }
+
+    pub fn identifiers(&self) -> &IdentifierRules {
+        &self.identifiers
+    }
+
+    /// The symbols of the `Symbol` tokens this language lexes, which `tokenize_with` adds
+    /// the identifiers of a file to.
+    pub fn interner(&self) -> &Interner {
+        &self.symbols
+    }
+}
+
+/// The token for a kind written as a `Token` variant name, such as `Let` or `Symbol(&&)`.
+///
+/// `IntType`, `FloatType` and `Symbol` without parentheses take `spelling` as their text;
+/// for the types it has to name one, as in `IntType(u8)`. The text of a `Symbol` is interned
+/// in `symbols`.
+pub fn token_kind(kind: &str, spelling: &str, symbols: &mut Interner) -> Option<Token> {
+    let (name, text) = match kind.split_once('(') {
+        Some((name, rest)) => (name, rest.strip_suffix(')')?),
+        None => (kind, spelling),
+    };
+    let text = text.to_string();
+    let token = match name {
+        "IntType" => Token::IntType(IntType::from_name(&text)?),
+        "FloatType" => Token::FloatType(FloatType::from_name(&text)?),
+        "Symbol" => Token::Symbol(symbols.intern(&text)),
+        _ if kind.contains('(') => return None,
+        "RBrace" => Token::RBrace,
+        "LBrace" => Token::LBrace,
+        "Semicolon" => Token::Semicolon,
+        "Colon" => Token::Colon,
+        "Equals" => Token::Equals,
+        "EqualEqual" => Token::EqualEqual,
+        "NotEquals" => Token::NotEquals,
+        "DoubleColon" => Token::DoubleColon,
+        "LessEqual" => Token::LessEqual,
+        "GreaterEqual" => Token::GreaterEqual,
+        "LeftParen" => Token::LeftParen,
+        "RightParen" => Token::RightParen,
+        "Minus" => Token::Minus,
+        "Not" => Token::Not,
+        "Plus" => Token::Plus,
+        "Slash" => Token::Slash,
+        "Star" => Token::Star,
+        "Question" => Token::Question,
+        "Percent" => Token::Percent,
+        "LBracket" => Token::LBracket,
+        "RBracket" => Token::RBracket,
+        "Power" => Token::Power,
+        "Arrow" => Token::Arrow,
+        "ReverseArrow" => Token::ReverseArrow,
+        "ChainNot" => Token::ChainNot,
+        "ChainBack" => Token::ChainBack,
+        "Less" => Token::Less,
+        "Greater" => Token::Greater,
+        "Let" => Token::Let,
+        "Op" => Token::Op,
+        "If" => Token::If,
+        "Else" => Token::Else,
+        "While" => Token::While,
+        "For" => Token::For,
+        "Loop" => Token::Loop,
+        "Success" => Token::Success,
+        "String" => Token::String,
+        "Char" => Token::Char,
+        "Bool" => Token::Bool,
+        "True" => Token::True,
+        "False" => Token::False,
+        "Break" => Token::Break,
+        "Continue" => Token::Continue,
+        "Nret" => Token::Nret,
+        "Data" => Token::Data,
+        "And" => Token::And,
+        "Or" => Token::Or,
+        _ => return None,
+    };
+    Some(token)
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorType::ParsingError)
+        .with_code(ErrorCode::InvalidLanguageSpec)
+        .with_message(format!(
+            "Invalid language definition! -> {}",
+            message.trim_end()
+        ))
+}
+
+fn kinds(name: &str, section: &Table, symbols: &mut Interner) -> Result<HashMap<String, Token>> {
+    section
+        .iter()
+        .map(|(spelling, kind)| {
+            let token = kind
+                .as_str()
+                .and_then(|kind| token_kind(kind, spelling, symbols))
+                .ok_or_else(|| {
+                    invalid(&format!("'{name}.{spelling}' is not a token kind: {kind}"))
+                })?;
+            if spelling.is_empty() || spelling.chars().any(char::is_whitespace) {
+                return Err(invalid(&format!(
+                    "'{name}' entries need a spelling without whitespace"
+                )));
+            }
+            Ok((spelling.clone(), token))
+        })
+        .collect()
+}
+
+fn text_of(section: &Table, key: &str) -> Result<Option<String>> {
+    match section.get(key) {
+        None => Ok(None),
+        Some(Value::String(text)) => Ok(Some(text.clone())),
+        Some(_) => Err(invalid(&format!("'{key}' has to be a string"))),
+    }
+}
+
+fn char_of(section: &Table, key: &str) -> Result<Option<char>> {
+    let Some(text) = text_of(section, key)? else {
+        return Ok(None);
+    };
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Some(c)),
+        _ => Err(invalid(&format!("'{key}' has to be a single character"))),
+    }
+}
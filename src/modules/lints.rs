@@ -0,0 +1,533 @@
+use unicode_script::{Script, UnicodeScript};
+use unicode_security::{MixedScript, confusable_detection::skeleton};
+
+use super::language::LanguageSpec;
+use super::source_map::{FileId, SourceFile};
+use super::token_stream::TokenStream;
+use super::tokens::{AnalyzedToken, IntType, Token};
+use crate::error_handling::{
+    Diagnostics, Error, ErrorCode, ErrorType::LexingError, Label, Level, Severity, Span, Suggestion,
+};
+
+/// Identifiers that are keywords elsewhere, with the `.lx` keyword to use instead.
+const KEYWORD_LIKE: [(&str, &str); 15] = [
+    ("int", "i32"),
+    ("integer", "i32"),
+    ("float", "f32"),
+    ("boolean", "bool"),
+    ("return", "success"),
+    ("fn", "op"),
+    ("func", "op"),
+    ("function", "op"),
+    ("def", "op"),
+    ("void", "nret"),
+    ("elif", "else if"),
+    ("elsif", "else if"),
+    ("True", "true"),
+    ("False", "false"),
+    ("struct", "data"),
+];
+
+/// The `KEYWORD_LIKE` entries whose keyword `spec` has, so a dialect is never told to use a
+/// keyword it lacks.
+fn keyword_like(spec: &LanguageSpec) -> Vec<(&'static str, &'static str)> {
+    KEYWORD_LIKE
+        .into_iter()
+        .filter(|(_, keyword)| {
+            keyword
+                .split(' ')
+                .all(|word| spec.keywords().any(|k| k == word))
+        })
+        .collect()
+}
+
+/// Runs every lint that is not allowed in `sink` over a file lexed with `spec`.
+pub fn lint(
+    file: FileId,
+    source: &SourceFile,
+    tokens: &TokenStream,
+    spec: &LanguageSpec,
+    sink: &mut Diagnostics,
+) {
+    let enabled = |sink: &Diagnostics, code| sink.level_of(code) != Level::Allow;
+
+    if enabled(sink, ErrorCode::UnreachableCode) {
+        let mut reach = Reachability {
+            tokens: tokens.iter().filter(|t| !t.token.is_comment()).collect(),
+            pos: 0,
+            file,
+            found: Vec::new(),
+        };
+        while reach.pos < reach.tokens.len() {
+            reach.block_body();
+            // a stray `}` at the top level, step over it and keep going
+            reach.pos += 1;
+        }
+        reach.found.into_iter().for_each(|w| sink.push(w));
+    }
+    if enabled(sink, ErrorCode::KeywordLikeIdentifier) {
+        keyword_like_identifiers(file, tokens, spec, sink);
+    }
+    if enabled(sink, ErrorCode::MisspelledKeyword) {
+        misspelled_keywords(file, tokens, spec, sink);
+    }
+    if enabled(sink, ErrorCode::ConfusableIdentifier) {
+        confusable_identifiers(file, source, tokens, sink);
+    }
+    if enabled(sink, ErrorCode::MixedIndentation) {
+        mixed_indentation(file, source, sink);
+    }
+    if enabled(sink, ErrorCode::TrailingWhitespace) {
+        trailing_whitespace(file, source, sink);
+    }
+}
+
+fn warning(code: ErrorCode, file: FileId, message: String, span: Span) -> Error {
+    Error::new(LexingError)
+        .with_severity(Severity::Warning)
+        .with_code(code)
+        .with_message(message)
+        .with_file(file)
+        .with_line(span.line)
+        .with_column(span.column)
+        .with_length(span.end_column.saturating_sub(span.column))
+}
+
+fn token_span(token: &AnalyzedToken) -> Span {
+    Span::new(token.line, token.column, token.length)
+}
+
+/// Walks the block structure of the token list looking for statements after a jump.
+struct Reachability<'a> {
+    tokens: Vec<&'a AnalyzedToken>,
+    pos: usize,
+    file: FileId,
+    found: Vec<Error>,
+}
+
+impl Reachability<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    /// Statements up to the closing `}` (left unconsumed); true if the block always jumps away.
+    fn block_body(&mut self) -> bool {
+        let mut jump: Option<(&AnalyzedToken, &str)> = None;
+        let mut unreachable: Option<(usize, usize)> = None;
+
+        while !matches!(self.peek(), None | Some(Token::RBrace)) {
+            let start = self.pos;
+            let jumps = self.statement();
+            if jump.is_some() {
+                let first = unreachable.map_or(start, |(first, _)| first);
+                unreachable = Some((first, self.pos - 1));
+            } else if jumps {
+                let what = match self.tokens[start].token {
+                    Token::If => "every branch of this `if` leaves the block",
+                    _ => "any code following this is unreachable",
+                };
+                jump = Some((self.tokens[start], what));
+            }
+        }
+
+        if let (Some((jump_token, what)), Some((first, last))) = (jump, unreachable) {
+            let (first, last) = (self.tokens[first], self.tokens[last]);
+            let span = Span::multiline(
+                first.line,
+                first.column,
+                last.line,
+                last.column + last.length,
+            );
+            let first_len = if span.is_multiline() {
+                first.length
+            } else {
+                span.end_column - span.column
+            };
+            self.found.push(
+                warning(
+                    ErrorCode::UnreachableCode,
+                    self.file,
+                    "Unreachable code!".to_string(),
+                    Span::new(first.line, first.column, first_len),
+                )
+                .with_label(Label::primary(span).with_message("unreachable code"))
+                .with_label(Label::secondary(token_span(jump_token)).with_message(what)),
+            );
+        }
+        jump.is_some()
+    }
+
+    /// One statement; true if control never continues past it.
+    fn statement(&mut self) -> bool {
+        match self.peek() {
+            Some(Token::Break | Token::Continue | Token::Success) => {
+                self.skip_statement();
+                true
+            }
+            Some(Token::If) => self.if_chain(),
+            Some(Token::LBrace) => self.block(),
+            _ => {
+                self.skip_statement();
+                false
+            }
+        }
+    }
+
+    /// `if` with its `else if`/`else` branches; it only jumps if there is an `else` and every
+    /// branch jumps.
+    fn if_chain(&mut self) -> bool {
+        self.pos += 1;
+        if !self.skip_to_block() {
+            return false;
+        }
+        let mut all_jump = self.block();
+        loop {
+            match self.peek() {
+                Some(Token::ElseIf) => self.pos += 1,
+                Some(Token::Else) => {
+                    self.pos += 1;
+                    if let Some(Token::If) = self.peek() {
+                        self.pos += 1;
+                    } else {
+                        return self.block() && all_jump;
+                    }
+                }
+                _ => return false,
+            }
+            if !self.skip_to_block() {
+                return false;
+            }
+            all_jump &= self.block();
+        }
+    }
+
+    /// `{ ... }` at the cursor; false if there is no block here.
+    fn block(&mut self) -> bool {
+        if !matches!(self.peek(), Some(Token::LBrace)) {
+            return false;
+        }
+        self.pos += 1;
+        let jumps = self.block_body();
+        if matches!(self.peek(), Some(Token::RBrace)) {
+            self.pos += 1;
+        }
+        jumps
+    }
+
+    /// Moves to the `{` opening the body of a condition; false if the statement ends first.
+    fn skip_to_block(&mut self) -> bool {
+        loop {
+            match self.peek() {
+                Some(Token::LBrace) => return true,
+                None | Some(Token::RBrace | Token::Semicolon) => return false,
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// Skips to the end of a plain statement: its `;`, or the end of the block it ends with.
+    fn skip_statement(&mut self) {
+        loop {
+            match self.peek() {
+                Some(Token::Semicolon) => {
+                    self.pos += 1;
+                    return;
+                }
+                Some(Token::LBrace) => {
+                    // statements ending in a block (`while`, `op`, `data`) still get checked
+                    self.block();
+                    return;
+                }
+                None | Some(Token::RBrace) => return,
+                _ => self.pos += 1,
+            }
+        }
+    }
+}
+
+fn keyword_like_identifiers(
+    file: FileId,
+    tokens: &TokenStream,
+    spec: &LanguageSpec,
+    sink: &mut Diagnostics,
+) {
+    let keyword_like = keyword_like(spec);
+    for token in tokens.iter() {
+        let Token::Identifier(name) = &token.token else {
+            continue;
+        };
+        let name = tokens.interner().resolve(*name);
+        if let Some((_, keyword)) = keyword_like.iter().find(|(like, _)| *like == name) {
+            sink.push(
+                warning(
+                    ErrorCode::KeywordLikeIdentifier,
+                    file,
+                    format!("Identifier '{name}' looks like a keyword!"),
+                    token_span(token),
+                )
+                .with_note(&format!(
+                    "'{name}' is not a keyword and is lexed as an identifier"
+                ))
+                .with_help(&format!("the keyword is '{keyword}'"))
+                .with_suggestion(
+                    Suggestion::new(token_span(token), keyword, "use the keyword")
+                        .maybe_incorrect(),
+                ),
+            );
+        }
+    }
+}
+
+fn misspelled_keywords(
+    file: FileId,
+    tokens: &TokenStream,
+    spec: &LanguageSpec,
+    sink: &mut Diagnostics,
+) {
+    let keyword_like = keyword_like(spec);
+    // (what the user probably meant, the keyword to use for it)
+    let candidates: Vec<(&str, &str)> = spec
+        .keyword_tokens()
+        // `size` or `u16` are names, not a misspelled `usize` or `i16`
+        .filter(|(_, token)| !matches!(token, Token::IntType(_)))
+        .map(|(k, _)| (k, k))
+        .chain(keyword_like.iter().copied())
+        .filter(|(meant, _)| meant.chars().count() >= 4 && IntType::from_name(meant).is_none())
+        .collect();
+
+    for token in tokens.iter() {
+        let Token::Identifier(name) = &token.token else {
+            continue;
+        };
+        let name = tokens.interner().resolve(*name);
+        let len = name.chars().count();
+        // look-alike letters from other scripts are reported by `confusable_identifiers`
+        if len < 4 || !name.is_ascii() || keyword_like.iter().any(|(like, _)| *like == name) {
+            continue;
+        }
+        let max_distance = if len <= 6 { 1 } else { 2 };
+        let best = candidates
+            .iter()
+            // `chars` or `loops` are names in their own right, not typos
+            .filter(|(meant, _)| !name.starts_with(meant))
+            .map(|(meant, keyword)| (edit_distance(name, meant), *meant, *keyword))
+            .filter(|(distance, _, _)| *distance <= max_distance)
+            .min();
+        let Some((_, meant, keyword)) = best else {
+            continue;
+        };
+
+        let mut warn = warning(
+            ErrorCode::MisspelledKeyword,
+            file,
+            format!("Identifier '{name}' looks like a misspelled keyword!"),
+            token_span(token),
+        )
+        .with_note(&format!("'{name}' is lexed as an identifier"));
+        if meant != keyword {
+            warn = warn.with_note(&format!(
+                "'{meant}' is spelled '{keyword}' in this language"
+            ));
+        }
+        sink.push(
+            warn.with_suggestion(
+                Suggestion::new(
+                    token_span(token),
+                    keyword,
+                    "a keyword with a similar name exists",
+                )
+                .maybe_incorrect(),
+            ),
+        );
+    }
+}
+
+fn confusable_identifiers(
+    file: FileId,
+    source: &SourceFile,
+    tokens: &TokenStream,
+    sink: &mut Diagnostics,
+) {
+    for token in tokens.iter() {
+        let Token::Identifier(name) = &token.token else {
+            continue;
+        };
+        let name = tokens.interner().resolve(*name);
+        if name.is_ascii() {
+            continue;
+        }
+        let looks_like: String = skeleton(name).collect();
+        let looks_ascii = looks_like.is_ascii() && looks_like != name;
+        if name.is_single_script() && !looks_ascii {
+            continue;
+        }
+
+        let mut scripts: Vec<Script> = Vec::new();
+        for script in name.chars().map(|c| c.script()) {
+            if !matches!(script, Script::Common | Script::Inherited) && !scripts.contains(&script) {
+                scripts.push(script);
+            }
+        }
+        let message = if scripts.len() > 1 {
+            let names: Vec<&str> = scripts.iter().map(|s| s.full_name()).collect();
+            format!(
+                "Identifier '{name}' mixes {} characters!",
+                names.join(" and ")
+            )
+        } else {
+            format!("Identifier '{name}' looks like '{looks_like}'!")
+        };
+        let mut warn = warning(
+            ErrorCode::ConfusableIdentifier,
+            file,
+            message,
+            token_span(token),
+        )
+        .with_label(Label::primary(token_span(token)));
+
+        // point at the look-alikes in the source spelling, which may not be normalized
+        let spelling = if looks_ascii {
+            source.line(token.line).unwrap_or("")
+        } else {
+            ""
+        };
+        for (i, c) in spelling
+            .chars()
+            .skip(token.column - 1)
+            .take(token.length)
+            .enumerate()
+        {
+            let prototype: String = skeleton(&c.to_string()).collect();
+            if c.is_ascii() || !prototype.is_ascii() || prototype == c.to_string() {
+                continue;
+            }
+            warn = warn.with_label(
+                Label::secondary(Span::new(token.line, token.column + i, 1)).with_message(
+                    &format!(
+                        "this is {} U+{:04X}, not Latin '{prototype}'",
+                        c.script().full_name(),
+                        c as u32
+                    ),
+                ),
+            );
+        }
+        if looks_ascii {
+            warn = warn.with_suggestion(
+                Suggestion::new(token_span(token), &looks_like, "retype it in Latin letters")
+                    .maybe_incorrect(),
+            );
+        }
+        sink.push(warn);
+    }
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions and swaps of two
+/// neighbouring chars each cost one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().flat_map(char::to_lowercase).collect();
+    let b: Vec<char> = b.chars().flat_map(char::to_lowercase).collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+fn mixed_indentation(file: FileId, source: &SourceFile, sink: &mut Diagnostics) {
+    let mut file_style: Option<(char, usize, usize)> = None;
+    let mut reported_style = false;
+
+    for line_no in 1..=source.line_count() {
+        let text = source.line(line_no).unwrap_or("");
+        if text.trim().is_empty() {
+            continue;
+        }
+        let indent: Vec<char> = text
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        let Some(&style) = indent.first() else {
+            continue;
+        };
+        let span = Span::new(line_no, 1, indent.len());
+
+        if indent.iter().any(|c| *c != style) {
+            sink.push(
+                warning(
+                    ErrorCode::MixedIndentation,
+                    file,
+                    "Mixed tabs and spaces in indentation!".to_string(),
+                    span,
+                )
+                .with_label(Label::primary(span).with_message("tabs and spaces mixed here")),
+            );
+            continue;
+        }
+        match file_style {
+            None => file_style = Some((style, line_no, indent.len())),
+            Some((first, first_line, first_len)) if first != style && !reported_style => {
+                let name = |c: char| if c == '\t' { "tabs" } else { "spaces" };
+                sink.push(
+                    warning(
+                        ErrorCode::MixedIndentation,
+                        file,
+                        format!(
+                            "Indented with {} but the file uses {}!",
+                            name(style),
+                            name(first)
+                        ),
+                        span,
+                    )
+                    .with_label(
+                        Label::secondary(Span::new(first_line, 1, first_len))
+                            .with_message(&format!("first indented with {} here", name(first))),
+                    ),
+                );
+                reported_style = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn trailing_whitespace(file: FileId, source: &SourceFile, sink: &mut Diagnostics) {
+    for line_no in 1..=source.line_count() {
+        let text = source.line(line_no).unwrap_or("");
+        let trailing = text
+            .chars()
+            .rev()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .count();
+        if trailing == 0 {
+            continue;
+        }
+        let column = text.chars().count() - trailing + 1;
+        sink.push(
+            warning(
+                ErrorCode::TrailingWhitespace,
+                file,
+                "Trailing whitespace!".to_string(),
+                Span::new(line_no, column, trailing),
+            )
+            .with_help("remove it, or run `enoc fmt`")
+            .with_suggestion(Suggestion::new(
+                Span::new(line_no, column, trailing),
+                "",
+                "remove the trailing whitespace",
+            )),
+        );
+    }
+}
@@ -0,0 +1,58 @@
+/// The unit columns are counted in when positions leave the lexer.
+///
+/// The lexer itself always counts chars (Unicode scalar values). Language server clients
+/// usually want UTF-16 code units and byte oriented tools want UTF-8 bytes. Columns stay
+/// 1-based in every encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionEncoding {
+    Utf8,
+    Utf16,
+    #[default]
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Parses the names used by the Language Server Protocol: `utf-8`, `utf-16`, `utf-32`.
+    pub fn from_name(name: &str) -> Option<PositionEncoding> {
+        match name {
+            "utf-8" => Some(PositionEncoding::Utf8),
+            "utf-16" => Some(PositionEncoding::Utf16),
+            "utf-32" => Some(PositionEncoding::Utf32),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            PositionEncoding::Utf8 => "utf-8",
+            PositionEncoding::Utf16 => "utf-16",
+            PositionEncoding::Utf32 => "utf-32",
+        }
+    }
+    /// How many units `c` takes up.
+    pub fn len_of(&self, c: char) -> usize {
+        match self {
+            PositionEncoding::Utf8 => c.len_utf8(),
+            PositionEncoding::Utf16 => c.len_utf16(),
+            PositionEncoding::Utf32 => 1,
+        }
+    }
+    pub fn len_of_str(&self, text: &str) -> usize {
+        match self {
+            PositionEncoding::Utf8 => text.len(),
+            _ => text.chars().map(|c| self.len_of(c)).sum(),
+        }
+    }
+}
+
+/// A 1-based line and column, the column counted in some `PositionEncoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Position {
+        Position { line, column }
+    }
+}
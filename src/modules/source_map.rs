@@ -0,0 +1,148 @@
+use super::file_reader::take_sources_as_string;
+use super::position::{Position, PositionEncoding};
+use crate::error_handling::{Error, Result};
+
+/// Handle to a file registered in a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(u32);
+
+/// A loaded source text with the byte offset of every line start.
+///
+/// Lines end at `\n`, `\r\n` or a lone `\r`; a leading byte order mark is dropped.
+pub struct SourceFile {
+    name: String,
+    text: String,
+    bom: bool,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: &str, text: String) -> SourceFile {
+        let bom = text.starts_with('\u{feff}');
+        let text = match text.strip_prefix('\u{feff}') {
+            Some(rest) => rest.to_string(),
+            None => text,
+        };
+        let bytes = text.as_bytes();
+        let mut line_starts = vec![0];
+        line_starts.extend(bytes.iter().enumerate().filter_map(|(i, b)| {
+            let ends_line = *b == b'\n' || (*b == b'\r' && bytes.get(i + 1) != Some(&b'\n'));
+            ends_line.then_some(i + 1)
+        }));
+        SourceFile {
+            name: name.to_owned(),
+            text,
+            bom,
+            line_starts,
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Whether the file started with a byte order mark, which `text` leaves out.
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+    /// Text of the 1-based line `line`, without its line terminator.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = match self.line_starts.get(line) {
+            Some(next) => next - 1,
+            None => self.text.len(),
+        };
+        let text = &self.text[start..end];
+        Some(text.strip_suffix('\r').unwrap_or(text))
+    }
+    /// Byte offset of the 1-based `line` and char `column`; one past the last char is allowed.
+    pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let text = self.line(line)?;
+        let column = column.checked_sub(1)?;
+        match text.char_indices().nth(column) {
+            Some((i, _)) => Some(start + i),
+            None if column == text.chars().count() => Some(start + text.len()),
+            None => None,
+        }
+    }
+
+    /// The char `column` of `line` counted in `encoding` instead.
+    pub fn encode_column(
+        &self,
+        line: usize,
+        column: usize,
+        encoding: PositionEncoding,
+    ) -> Option<usize> {
+        let text = self.line(line)?;
+        let before = column.checked_sub(1)?;
+        if before > text.chars().count() {
+            return None;
+        }
+        let units: usize = text.chars().take(before).map(|c| encoding.len_of(c)).sum();
+        Some(units + 1)
+    }
+    /// The char column of a `column` counted in `encoding`; a column that falls inside a
+    /// char maps to that char.
+    pub fn decode_column(
+        &self,
+        line: usize,
+        column: usize,
+        encoding: PositionEncoding,
+    ) -> Option<usize> {
+        let text = self.line(line)?;
+        let target = column.checked_sub(1)?;
+        let mut units = 0;
+        for (i, c) in text.chars().enumerate() {
+            let next = units + encoding.len_of(c);
+            if target < next {
+                return Some(i + 1);
+            }
+            units = next;
+        }
+        (target == units).then_some(text.chars().count() + 1)
+    }
+    /// The position `chars` chars after `line`/`column`, a line break counting as one char.
+    pub fn advance(&self, line: usize, column: usize, chars: usize) -> Option<Position> {
+        let (mut line, mut column, mut left) = (line, column, chars);
+        loop {
+            let len = self.line(line)?.chars().count();
+            let room = (len + 1).checked_sub(column)?;
+            if left <= room {
+                return Some(Position::new(line, column + left));
+            }
+            // step over the rest of the line and its line break
+            left -= room + 1;
+            line += 1;
+            column = 1;
+        }
+    }
+}
+
+/// Every source the lexer has seen, so diagnostics render from exactly the lexed text.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap::default()
+    }
+    /// Registers in-memory source text, e.g. from stdin or an editor buffer.
+    pub fn add(&mut self, name: &str, text: String) -> FileId {
+        self.files.push(SourceFile::new(name, text));
+        FileId(self.files.len() as u32 - 1)
+    }
+    pub fn load(&mut self, path: &str) -> Result<FileId> {
+        let text = take_sources_as_string(path).map_err(|e| Error::io(e, path))?;
+        Ok(self.add(path, text))
+    }
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0 as usize]
+    }
+}
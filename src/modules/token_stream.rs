@@ -1,32 +1,269 @@
-//use crate::error_handling::Result;
-use super::tokens::AnalyzedToken;
+use std::ops::{Index, Range};
+use std::slice::SliceIndex;
 
+use super::analyser::{AnalyzedChar, CharKind};
+use super::interner::Interner;
+use super::position::Position;
+use super::source_map::FileId;
+use super::tokens::{AnalyzedToken, Token, TokenKind};
+use crate::error_handling::{Error, ErrorCode, ErrorType, Label, Result, Span};
+
+/// The tokens of a file in source order, each starting at or after the one before it.
+///
+/// Positions count columns in chars, like the tokens themselves.
 pub struct TokenStream {
     token_list: Vec<AnalyzedToken>,
+    // where each token ends, parallel to `token_list`
+    extents: Vec<Extent>,
+    eof: AnalyzedToken,
+    file: Option<FileId>,
+    // the texts of the identifiers and symbols of the tokens
+    interner: Interner,
+}
+
+/// The end of a token, and its bytes once the chars it was lexed from are known.
+#[derive(Clone)]
+pub(crate) struct Extent {
+    pub(crate) end: Position,
+    pub(crate) bytes: Option<Range<usize>>,
 }
 
 impl TokenStream {
+    /// A stream of tokens that each fit on their line, ending right after the last one.
+    /// `with_chars` gives exact ends and byte offsets.
     pub fn new(list: Vec<AnalyzedToken>) -> TokenStream {
-        TokenStream { token_list: list }
+        let extents = list
+            .iter()
+            .map(|t| Extent {
+                end: Position::new(t.line, t.column + t.length),
+                bytes: None,
+            })
+            .collect();
+        let end = list.last().map_or(Position::new(1, 1), |t| {
+            Position::new(t.line, t.column + t.length)
+        });
+        TokenStream {
+            token_list: list,
+            extents,
+            eof: eof_at(end),
+            file: None,
+            interner: Interner::new(),
+        }
+    }
+    /// Takes the end of every token, its byte offsets and the end of the file from the
+    /// chars the tokens were lexed from.
+    pub fn with_chars(mut self, chars: &[AnalyzedChar]) -> Self {
+        let index_of = |line: usize, column: usize| {
+            chars.partition_point(|c| (c.line, c.column) < (line, column))
+        };
+        // the position and byte offset just past the char at `i`
+        let after = |i: usize| match chars.get(i + 1) {
+            Some(next) => (Position::new(next.line, next.column), next.offset),
+            None => {
+                let c = &chars[i];
+                let len = match c.kind {
+                    CharKind::Char(ch) | CharKind::Whitespace(ch) => ch.len_utf8(),
+                    CharKind::Newline => 1,
+                };
+                let end = match c.kind {
+                    CharKind::Newline => Position::new(c.line + 1, 1),
+                    _ => Position::new(c.line, c.column + 1),
+                };
+                (end, c.offset + len)
+            }
+        };
+        self.extents = self
+            .token_list
+            .iter()
+            .map(|t| {
+                let first = index_of(t.line, t.column);
+                match (chars.get(first), t.length) {
+                    (Some(start), 1..) if first + t.length <= chars.len() => {
+                        let (end, end_offset) = after(first + t.length - 1);
+                        Extent {
+                            end,
+                            bytes: Some(start.offset..end_offset),
+                        }
+                    }
+                    _ => Extent {
+                        end: Position::new(t.line, t.column + t.length),
+                        bytes: None,
+                    },
+                }
+            })
+            .collect();
+        let end = match chars.len() {
+            0 => Position::new(1, 1),
+            len => after(len - 1).0,
+        };
+        self.eof = eof_at(end);
+        self
+    }
+    /// A stream from tokens and extents that already belong together.
+    pub(crate) fn from_parts(
+        list: Vec<AnalyzedToken>,
+        extents: Vec<Extent>,
+        eof: AnalyzedToken,
+        file: Option<FileId>,
+        interner: Interner,
+    ) -> TokenStream {
+        TokenStream {
+            token_list: list,
+            extents,
+            eof,
+            file,
+            interner,
+        }
+    }
+    pub(crate) fn extents(&self) -> &[Extent] {
+        &self.extents
+    }
+    /// The file the tokens were lexed from, for the errors of a `TokenCursor`.
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+    pub fn file(&self) -> Option<FileId> {
+        self.file
+    }
+    /// The interner the symbols of the tokens resolve in.
+    pub fn with_interner(mut self, interner: Interner) -> Self {
+        self.interner = interner;
+        self
+    }
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+    pub(crate) fn take_interner(&mut self) -> Interner {
+        std::mem::take(&mut self.interner)
     }
     pub fn iter(&self) -> TokenStreamIter<'_> {
         TokenStreamIter {
             token_list: &self.token_list,
             index: 0,
+            end: self.token_list.len(),
         }
     }
+    pub fn len(&self) -> usize {
+        self.token_list.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.token_list.is_empty()
+    }
+    pub fn as_slice(&self) -> &[AnalyzedToken] {
+        &self.token_list
+    }
+    /// The bytes of the token at `index`, if the stream was built `with_chars`.
+    pub fn byte_range(&self, index: usize) -> Option<Range<usize>> {
+        self.extents.get(index)?.bytes.clone()
+    }
+    /// The token that covers the char at `line`/`column`, if any does.
+    ///
+    /// A comment between `else` and `if` lies inside the `ElseIf` token; it is the comment
+    /// that is returned for its chars.
+    pub fn token_at(&self, line: usize, column: usize) -> Option<&AnalyzedToken> {
+        let at = Position::new(line, column);
+        let after = self
+            .token_list
+            .partition_point(|t| Position::new(t.line, t.column) <= at);
+        self.covering(after, |i| at < self.extents[i].end)
+    }
+    /// The token that covers the byte at `offset`, if any does. Only streams built
+    /// `with_chars` know the bytes of their tokens.
+    pub fn token_at_offset(&self, offset: usize) -> Option<&AnalyzedToken> {
+        let after = self
+            .extents
+            .partition_point(|e| e.bytes.as_ref().is_some_and(|bytes| bytes.start <= offset));
+        self.covering(after, |i| {
+            self.extents[i]
+                .bytes
+                .as_ref()
+                .is_some_and(|bytes| offset < bytes.end)
+        })
+    }
+    /// The innermost token before index `after` that `covers`: the last one, or the
+    /// `ElseIf` that the comments before it lie in.
+    fn covering(&self, after: usize, covers: impl Fn(usize) -> bool) -> Option<&AnalyzedToken> {
+        (0..after)
+            .rev()
+            .take_while(|&i| i + 1 == after || self.token_list[i + 1].token.is_comment())
+            .find(|&i| covers(i))
+            .map(|i| &self.token_list[i])
+    }
+    /// Every token with a char in `range`, in order.
+    pub fn tokens_in_range(&self, range: Range<Position>) -> &[AnalyzedToken] {
+        let start = |t: &AnalyzedToken| Position::new(t.line, t.column);
+        let mut first = self.token_list.partition_point(|t| start(t) < range.start);
+        // tokens that start before the range but reach into it, like an `ElseIf` with a
+        // comment in it
+        for i in (0..first).rev() {
+            if self.extents[i].end > range.start {
+                first = i;
+            } else if !self.token_list[i].token.is_comment() {
+                break;
+            }
+        }
+        let last = self.token_list.partition_point(|t| start(t) < range.end);
+        &self.token_list[first..last.max(first)]
+    }
+    /// The `Eof` token at the end of the file.
+    pub fn eof(&self) -> &AnalyzedToken {
+        &self.eof
+    }
+    pub fn cursor(&self) -> TokenCursor<'_> {
+        TokenCursor {
+            tokens: self
+                .token_list
+                .iter()
+                .filter(|t| !t.token.is_comment())
+                .collect(),
+            eof: &self.eof,
+            file: self.file,
+            interner: &self.interner,
+            pos: 0,
+        }
+    }
+}
+
+impl<I: SliceIndex<[AnalyzedToken]>> Index<I> for TokenStream {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.token_list[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a TokenStream {
+    type Item = &'a AnalyzedToken;
+    type IntoIter = TokenStreamIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+fn eof_at(end: Position) -> AnalyzedToken {
+    AnalyzedToken {
+        token: Token::Eof,
+        line: end.line,
+        column: end.column,
+        length: 0,
+        soft_keyword: None,
+    }
 }
 
 pub struct TokenStreamIter<'a> {
     token_list: &'a [AnalyzedToken],
     index: usize,
+    // one past the last token not yet returned from the back
+    end: usize,
 }
 
 impl<'a> Iterator for TokenStreamIter<'a> {
     type Item = &'a AnalyzedToken;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.token_list.len() {
+        if self.index >= self.end {
             None
         } else {
             let item = &self.token_list[self.index];
@@ -34,4 +271,114 @@ impl<'a> Iterator for TokenStreamIter<'a> {
             Some(item)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.index;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for TokenStreamIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some(&self.token_list[self.end])
+        }
+    }
+}
+
+impl ExactSizeIterator for TokenStreamIter<'_> {}
+
+/// Where a `TokenCursor` was, to go back to with `rewind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// Reads a `TokenStream` the way a parser does: looking ahead, consuming the tokens it
+/// expects and backtracking to a checkpoint.
+///
+/// Comments, doc comments included, are skipped. Past the last token the cursor stays at
+/// the stream's `Eof` token. `at`, `eat` and `expect` go by `TokenKind`, so any identifier
+/// or number matches; `at_token` and `eat_token` compare the whole token, for one
+/// particular symbol such as `,` from the stream's interner.
+pub struct TokenCursor<'a> {
+    tokens: Vec<&'a AnalyzedToken>,
+    eof: &'a AnalyzedToken,
+    file: Option<FileId>,
+    interner: &'a Interner,
+    pos: usize,
+}
+
+impl<'a> TokenCursor<'a> {
+    /// The next token, without consuming it.
+    pub fn peek(&self) -> &'a AnalyzedToken {
+        self.peek_nth(0)
+    }
+    /// The token `n` places after the next one; `peek_nth(0)` is `peek()`.
+    pub fn peek_nth(&self, n: usize) -> &'a AnalyzedToken {
+        self.tokens.get(self.pos + n).copied().unwrap_or(self.eof)
+    }
+    /// Consumes the next token and returns it.
+    pub fn bump(&mut self) -> &'a AnalyzedToken {
+        let token = self.peek();
+        self.pos = (self.pos + 1).min(self.tokens.len());
+        token
+    }
+    pub fn at(&self, kind: TokenKind) -> bool {
+        self.peek().token.kind() == kind
+    }
+    pub fn at_token(&self, token: &Token) -> bool {
+        self.peek().token == *token
+    }
+    pub fn at_eof(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+    /// Consumes the next token if it is of `kind`.
+    pub fn eat(&mut self, kind: TokenKind) -> bool {
+        let found = self.at(kind);
+        if found {
+            self.bump();
+        }
+        found
+    }
+    /// Consumes the next token if it is `token`, payload included.
+    pub fn eat_token(&mut self, token: &Token) -> bool {
+        let found = self.at_token(token);
+        if found {
+            self.bump();
+        }
+        found
+    }
+    /// Consumes the next token if it is of `kind`, or reports the token found in its place.
+    pub fn expect(&mut self, kind: TokenKind) -> Result<&'a AnalyzedToken> {
+        if self.at(kind) {
+            return Ok(self.bump());
+        }
+        let found = self.peek();
+        // `Eof` has no chars, point at the position it stands for
+        let length = found.length.max(1);
+        let span = Span::new(found.line, found.column, length);
+        let mut err = Error::new(ErrorType::ParsingError)
+            .with_code(ErrorCode::UnexpectedToken)
+            .with_message(format!(
+                "Unexpected token! -> expected {kind}, found {}",
+                self.interner.resolved(&found.token)
+            ))
+            .with_line(found.line)
+            .with_column(found.column)
+            .with_length(length)
+            .with_label(Label::primary(span).with_message(&format!("expected {kind}")));
+        if let Some(file) = self.file {
+            err = err.with_file(file);
+        }
+        Err(err)
+    }
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.pos)
+    }
+    /// Goes back to where the cursor was at `checkpoint`.
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.pos = checkpoint.0;
+    }
 }
@@ -1,107 +1,281 @@
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
+
 use super::analyser::{AnalyzedChar, CharKind};
+use super::interner::{Interner, Symbol};
+use super::language::LanguageSpec;
+use super::source_map::FileId;
 use super::token_stream::TokenStream;
-use super::tokens::{AnalyzedToken, SYMBOLS, Token};
-use crate::error_handling::{Error, ErrorType::LexingError, Result};
+use super::tokens::{AnalyzedToken, Token};
+use crate::error_handling::{
+    Error, ErrorCode, ErrorType::LexingError, Label, Result, Span, Suggestion,
+};
 
-pub fn tokenize(chars: &[AnalyzedChar], path: &str) -> Result<TokenStream> {
-    let mut char_buf: Vec<&AnalyzedChar> = Vec::new();
-    let mut string_buf = String::new();
-    let mut result_vec: Vec<AnalyzedToken> = Vec::new();
+#[derive(Debug, Clone, Default)]
+pub struct TokenizeOptions {
+    keep_comments: bool,
+    split_else_if: bool,
+    language: LanguageSpec,
+}
 
+impl TokenizeOptions {
+    pub fn new() -> TokenizeOptions {
+        TokenizeOptions::default()
+    }
+    /// Emit `//` comments as `Token::Comment` instead of discarding them.
+    /// Doc comments (`///` and `//!`) are always emitted.
+    pub fn with_comments(mut self, keep: bool) -> Self {
+        self.keep_comments = keep;
+        self
+    }
+    /// Keep `else if` as an `Else` and an `If` token instead of one `ElseIf`, for tools
+    /// that need every keyword where it was written.
+    pub fn with_split_else_if(mut self, split: bool) -> Self {
+        self.split_else_if = split;
+        self
+    }
+    /// Lex `language` instead of the built-in `.lx` one.
+    pub fn with_language(mut self, language: LanguageSpec) -> Self {
+        self.language = language;
+        self
+    }
+    pub fn language(&self) -> &LanguageSpec {
+        &self.language
+    }
+    pub fn keep_comments(&self) -> bool {
+        self.keep_comments
+    }
+}
+
+pub fn tokenize(chars: &[AnalyzedChar], file: FileId) -> Result<TokenStream> {
+    tokenize_with(chars, file, &TokenizeOptions::default())
+}
+
+pub fn tokenize_with(
+    chars: &[AnalyzedChar],
+    file: FileId,
+    options: &TokenizeOptions,
+) -> Result<TokenStream> {
+    tokenize_in(chars, file, options, options.language.interner().clone())
+}
+
+/// Lexes `chars` interning names in `interner`, which has to start with the symbols of
+/// `options.language()`. The stream owns the interner afterwards.
+pub(crate) fn tokenize_in(
+    chars: &[AnalyzedChar],
+    file: FileId,
+    options: &TokenizeOptions,
+    mut interner: Interner,
+) -> Result<TokenStream> {
+    let mut result_vec: Vec<AnalyzedToken> = Vec::new();
     let mut iter = chars.iter().peekable();
+    lex(
+        &mut iter,
+        file,
+        options,
+        &mut interner,
+        &mut result_vec,
+        None,
+    )?;
+    if !options.split_else_if {
+        result_vec = fuse_else_if(result_vec, chars);
+    }
+    Ok(TokenStream::new(result_vec)
+        .with_chars(chars)
+        .with_file(file)
+        .with_interner(interner))
+}
+
+/// Replaces every `else` followed by `if` with one `ElseIf` spanning both keywords.
+///
+/// Only whitespace and comments may come between them; comments in between are kept and
+/// follow the `ElseIf`. Only line comments count: `.lx` has no block comments, and
+/// `else /* c */ if` stays `Else`, `/*` and the rest, as `/*` is lexed as an operator.
+fn fuse_else_if(tokens: Vec<AnalyzedToken>, chars: &[AnalyzedChar]) -> Vec<AnalyzedToken> {
+    let offset = |token: &AnalyzedToken| {
+        chars.partition_point(|c| (c.line, c.column) < (token.line, token.column))
+    };
+    let mut fused: Vec<AnalyzedToken> = Vec::with_capacity(tokens.len());
+    // where the last `else` went in `fused`, while only comments follow it
+    let mut else_at: Option<usize> = None;
+    for token in tokens {
+        match token.token {
+            Token::If if else_at.is_some() => {
+                let else_token = &mut fused[else_at.take().unwrap()];
+                else_token.length = offset(&token) + token.length - offset(else_token);
+                else_token.token = Token::ElseIf;
+                continue;
+            }
+            Token::Else => else_at = Some(fused.len()),
+            _ if token.token.is_comment() => {}
+            _ => else_at = None,
+        }
+        fused.push(token);
+    }
+    fused
+}
+
+type Chars<'a> = std::iter::Peekable<std::slice::Iter<'a, AnalyzedChar>>;
+
+/// Lexes tokens into `result_vec` until the end of the input, or, for the expression of an
+/// interpolated string opened at `open`, until the `}` that closes it.
+fn lex<'a>(
+    iter: &mut Chars<'a>,
+    file: FileId,
+    options: &TokenizeOptions,
+    interner: &mut Interner,
+    result_vec: &mut Vec<AnalyzedToken>,
+    open: Option<&'a AnalyzedChar>,
+) -> Result<()> {
+    let spec = &options.language;
+    let (quote, char_quote) = (spec.string_quote(), spec.char_quote());
+    let mut char_buf: Vec<&AnalyzedChar> = Vec::new();
+    let mut string_buf = String::new();
+    // the chars from a marker start on, reused so that operators allocate nothing
+    let mut ahead = String::new();
+    // braces opened inside an interpolation, which its closing `}` has to skip
+    let mut depth = 0;
+    let mut last = open;
 
     while let Some(curr) = iter.next() {
+        last = Some(curr);
         match &curr.kind {
-            CharKind::Whitespace | CharKind::Newline => {
-                char_buf_clear(&mut char_buf, &mut string_buf, &mut result_vec, path)?;
+            CharKind::Newline if open.is_some() => {
+                return Err(unclosed_interpolation(open.unwrap(), curr, file));
+            }
+            CharKind::Whitespace(_) | CharKind::Newline => {
+                char_buf_clear(
+                    &mut char_buf,
+                    &mut string_buf,
+                    result_vec,
+                    file,
+                    spec,
+                    interner,
+                )?;
             }
             CharKind::Char(c) => {
-                if *c == '"' {
-                    char_buf_clear(&mut char_buf, &mut string_buf, &mut result_vec, path)?;
-                    let lit = handle_string_literal(&mut iter, curr.line, curr.column, path)?;
+                if open.is_some() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' if depth == 0 => {
+                            char_buf_clear(
+                                &mut char_buf,
+                                &mut string_buf,
+                                result_vec,
+                                file,
+                                spec,
+                                interner,
+                            )?;
+                            result_vec.push(AnalyzedToken {
+                                token: Token::InterpolationEnd,
+                                line: curr.line,
+                                column: curr.column,
+                                length: 1,
+                                soft_keyword: None,
+                            });
+                            return Ok(());
+                        }
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                }
+                if *c == quote {
+                    if let Some(hashes) = raw_prefix(&char_buf) {
+                        // `r` and the hashes are part of the literal, not an identifier
+                        let (line, column) = (char_buf[0].line, char_buf[0].column);
+                        char_buf.clear();
+                        let lit = handle_raw_string(iter, line, column, hashes, quote, file)?;
+                        result_vec.push(lit);
+                        continue;
+                    }
+                    char_buf_clear(
+                        &mut char_buf,
+                        &mut string_buf,
+                        result_vec,
+                        file,
+                        spec,
+                        interner,
+                    )?;
+                    let mut ahead = iter.clone();
+                    let is_quote = |c: Option<&AnalyzedChar>| {
+                        c.is_some_and(|c| matches!(c.kind, CharKind::Char(q) if q == quote))
+                    };
+                    let lit = if is_quote(ahead.next()) && is_quote(ahead.next()) {
+                        handle_multiline_string(iter, curr.line, curr.column, quote, file)?
+                    } else {
+                        handle_string_literal(iter, curr, file, options, interner, result_vec)?;
+                        continue;
+                    };
                     result_vec.push(lit);
-                } else if *c == '\'' {
-                    char_buf_clear(&mut char_buf, &mut string_buf, &mut result_vec, path)?;
-                    let lit = handle_char_literal(&mut iter, curr.line, curr.column, path)?;
+                } else if *c == char_quote {
+                    char_buf_clear(
+                        &mut char_buf,
+                        &mut string_buf,
+                        result_vec,
+                        file,
+                        spec,
+                        interner,
+                    )?;
+                    let lit = handle_char_literal(iter, curr.line, curr.column, char_quote, file)?;
                     result_vec.push(lit);
-                } else if is_symbol_start(*c) {
-                    char_buf_clear(&mut char_buf, &mut string_buf, &mut result_vec, path)?;
-
-                    if let Some(next) = iter.peek() {
-                        if let CharKind::Char(nc) = next.kind {
-                            if let Some(symbol) = try_two_char_symbol(*c, nc) {
-                                if symbol == ("//") {
-                                    handle_command_line(&mut iter)?;
-                                } else {
-                                    let mut t = Token::Symbol(symbol.clone());
-                                    if symbol == ("==") {
-                                        t = Token::Equals;
-                                    } else if symbol == ("!=") {
-                                        t = Token::NotEquals;
-                                    } else if symbol == ("&&") {
-                                        t = Token::And;
-                                    } else if symbol == ("||") {
-                                        t = Token::Or;
-                                    } else if symbol == ("<=") {
-                                        t = Token::LessEqual;
-                                    } else if symbol == (">=") {
-                                        t = Token::GreaterEqual;
-                                    } else if symbol == ("->") {
-                                        t = Token::Arrow;
-                                    } else if symbol == ("-<") {
-                                        t = Token::ChainBack;
-                                    } else if symbol == ("-!") {
-                                        t = Token::ChainNot;
-                                    } else if symbol == ("<-") {
-                                        t = Token::ReverseArrow;
-                                    } else if symbol == ("::") {
-                                        t = Token::DoubleColon;
-                                    }
-                                    result_vec.push(AnalyzedToken {
-                                        token: t,
-                                        line: curr.line,
-                                        column: curr.column,
-                                        length: 2,
-                                    });
-                                    iter.next();
-                                }
-                                continue;
-                            }
+                } else if spec.is_marker_start(*c) {
+                    char_buf_clear(
+                        &mut char_buf,
+                        &mut string_buf,
+                        result_vec,
+                        file,
+                        spec,
+                        interner,
+                    )?;
+
+                    ahead.clear();
+                    ahead.extend(
+                        std::iter::once(*c)
+                            .chain(iter.clone().map_while(|c| match c.kind {
+                                CharKind::Char(c) => Some(c),
+                                _ => None,
+                            }))
+                            .take(spec.longest_marker()),
+                    );
+                    if spec.is_comment(&ahead) {
+                        let comment = handle_comment(iter, curr, spec);
+                        if options.keep_comments || !matches!(comment.token, Token::Comment(_)) {
+                            result_vec.push(comment);
                         }
+                    } else if let Some((spelling, token)) = spec.operator_at(&ahead) {
+                        let length = spelling.chars().count();
+                        // the first char is `curr`, the rest are still waiting in the iterator
+                        for _ in 1..length {
+                            iter.next();
+                        }
+                        result_vec.push(AnalyzedToken {
+                            token,
+                            line: curr.line,
+                            column: curr.column,
+                            length,
+                            soft_keyword: None,
+                        });
+                    } else {
+                        // a marker starts here but does not match in full
+                        char_buf.push(curr);
                     }
-                    let this_token = match c {
-                        ';' => Token::Semicolon,
-                        '=' => Token::Equals,
-                        ':' => Token::Colon,
-                        '{' => Token::LBrace,
-                        '}' => Token::RBrace,
-                        ')' => Token::RightParen,
-                        '(' => Token::LeftParen,
-                        '[' => Token::LBracket,
-                        ']' => Token::RBracket,
-                        '+' => Token::Plus,
-                        '-' => Token::Minus,
-                        '*' => Token::Star,
-                        '/' => Token::Slash,
-                        '%' => Token::Percent,
-                        '^' => Token::Power,
-                        '?' => Token::Question,
-                        '!' => Token::Not,
-                        _ => Token::Symbol(c.to_string()),
-                    };
-                    result_vec.push(AnalyzedToken {
-                        token: this_token,
-                        line: curr.line,
-                        column: curr.column,
-                        length: 1,
-                    });
                 } else {
                     char_buf.push(curr);
                 }
             }
         }
     }
-    Ok(TokenStream::new(result_vec))
+    char_buf_clear(
+        &mut char_buf,
+        &mut string_buf,
+        result_vec,
+        file,
+        spec,
+        interner,
+    )?;
+    match open {
+        Some(open) => Err(unclosed_interpolation(open, last.unwrap_or(open), file)),
+        None => Ok(()),
+    }
 }
 
 fn final_buffer(
@@ -109,24 +283,22 @@ fn final_buffer(
     buf: &mut String,
     lin: usize,
     col: usize,
-    path: &str,
+    file: FileId,
+    spec: &LanguageSpec,
+    interner: &mut Interner,
 ) -> Result<()> {
     if !buf.is_empty() {
-        let t = token_of(buf, lin, col, path)?;
-        if let Token::If = t {
-            let last_token = tokens.last_mut().unwrap();
-            if let Token::Else = last_token.token {
-                last_token.token = Token::ElseIf;
-                last_token.length = 7;
-                buf.clear();
-                return Ok(());
-            }
-        }
+        let t = token_of(buf, lin, col, file, spec, interner)?;
+        let soft_keyword = match &t {
+            Token::Identifier(_) => spec.contextual_keyword(buf),
+            _ => None,
+        };
         tokens.push(AnalyzedToken {
             token: t,
             line: lin,
             column: col,
-            length: buf.len(),
+            length: buf.chars().count(),
+            soft_keyword,
         });
 
         buf.clear();
@@ -134,163 +306,572 @@ fn final_buffer(
     Ok(())
 }
 
-fn token_of(st: &str, t_lin: usize, t_col: usize, path: &str) -> Result<Token> {
-    if SYMBOLS.contains(st) {
-        Ok(Token::Symbol(st.to_string()))
+fn token_of(
+    st: &str,
+    t_lin: usize,
+    t_col: usize,
+    file: FileId,
+    spec: &LanguageSpec,
+    interner: &mut Interner,
+) -> Result<Token> {
+    if let Some(keyword) = spec.keyword(st) {
+        Ok(keyword)
+    } else if let Some(name) = st.strip_prefix("r#") {
+        // `r#loop` is the identifier `loop`, whatever the keyword table says
+        raw_identifier(name, t_lin, t_col, file, spec, interner)
     } else if st.chars().all(|c| c.is_ascii_digit()) {
-        Ok(Token::Number(st.parse::<i32>().unwrap()))
+        match st.parse::<i32>() {
+            Ok(n) => Ok(Token::Number(n)),
+            Err(_) => Err(Error::new(LexingError)
+                .with_code(ErrorCode::IntegerOverflow)
+                .with_message(format!("Integer literal is too large for i32! -> '{st}'"))
+                .with_line(t_lin)
+                .with_column(t_col)
+                .with_file(file)
+                .with_length(st.len())
+                .with_note("integer literals must be at most 2147483647")),
+        }
+    } else if is_valid_identifier(st, t_lin, t_col, file, spec)? {
+        // `é` typed precomposed or as `e` + accent is the same name
+        Ok(Token::Identifier(intern_name(interner, st)))
     } else {
-        match st {
-            "let" => Ok(Token::Let),
-            "op" => Ok(Token::Op),
-            "if" => Ok(Token::If),
-            "else" => Ok(Token::Else),
-            "while" => Ok(Token::While),
-            "for" => Ok(Token::For),
-            "loop" => Ok(Token::Loop),
-            "success" => Ok(Token::Success),
-            "i32" => Ok(Token::IntType("i32".to_string())),
-            "string" => Ok(Token::String),
-            "char" => Ok(Token::Char),
-            "f32" => Ok(Token::FloatType("f32".to_string())),
-            "bool" => Ok(Token::Bool),
-            "break" => Ok(Token::Break),
-            "continue" => Ok(Token::Continue),
-            "true" => Ok(Token::True),
-            "false" => Ok(Token::False),
-            "nret" => Ok(Token::Nret),
-            "data" => Ok(Token::Data),
-            "and" => Ok(Token::Symbol("&&".to_string())),
-            "or" => Ok(Token::Symbol("||".to_string())),
-            _ => {
-                if is_valid_identifier(st, t_lin, t_col, path)? {
-                    Ok(Token::Identifier(st.to_string()))
-                } else {
-                    Err(Error::new(LexingError)
-                        .with_message(format!("Unexpected token! -> '{}'", st))
-                        .with_line(t_lin)
-                        .with_column(t_col)
-                        .with_file_path(path))
-                }
-            }
+        let mut err = Error::new(LexingError)
+            .with_code(ErrorCode::UnexpectedCharacter)
+            .with_message(format!("Unexpected token! -> '{}'", st))
+            .with_line(t_lin)
+            .with_column(t_col)
+            .with_file(file)
+            .with_length(st.chars().count());
+        let rules = spec.identifiers();
+        if let Some((i, c)) = st.chars().enumerate().find(|(i, c)| match i {
+            0 => !rules.is_start(*c),
+            _ => !rules.is_continue(*c),
+        }) {
+            err = err.with_label(Label::primary(Span::new(t_lin, t_col + i, 1)).with_message(
+                &format!(
+                    "{:?} (U+{:04X}) cannot be part of an identifier",
+                    c, c as u32
+                ),
+            ));
         }
+        Err(err)
+    }
+}
+
+/// The identifier of a raw identifier `r#name`, given `name`.
+fn raw_identifier(
+    name: &str,
+    lin: usize,
+    col: usize,
+    file: FileId,
+    spec: &LanguageSpec,
+    interner: &mut Interner,
+) -> Result<Token> {
+    let rules = spec.identifiers();
+    let mut chars = name.chars();
+    let valid =
+        chars.next().is_some_and(|c| rules.is_start(c)) && chars.all(|c| rules.is_continue(c));
+    if !valid {
+        return Err(Error::new(LexingError)
+            .with_code(ErrorCode::UnexpectedCharacter)
+            .with_message(format!("Invalid raw identifier! -> 'r#{name}'"))
+            .with_line(lin)
+            .with_column(col)
+            .with_file(file)
+            .with_length(name.chars().count() + 2)
+            .with_help("'r#' has to be followed by an identifier, as in 'r#loop'"));
+    }
+    Ok(Token::Identifier(intern_name(interner, name)))
+}
+
+/// The symbol of an identifier in NFC, which only needs a new `String` if `name` is not.
+fn intern_name(interner: &mut Interner, name: &str) -> Symbol {
+    match is_nfc_quick(name.chars()) {
+        IsNormalized::Yes => interner.intern(name),
+        _ => interner.intern(&name.nfc().collect::<String>()),
     }
 }
 
-fn is_valid_identifier(st: &str, lin: usize, col: usize, path: &str) -> Result<bool> {
+fn is_valid_identifier(
+    st: &str,
+    lin: usize,
+    col: usize,
+    file: FileId,
+    spec: &LanguageSpec,
+) -> Result<bool> {
     if st.is_empty() {
         return Ok(false);
     }
     let first_char = st.chars().next().unwrap();
-    if !first_char.is_alphabetic() && first_char != '_' {
+    if first_char.is_numeric() {
         return Err(Error::new(LexingError)
+            .with_code(ErrorCode::IdentifierStartsWithDigit)
             .with_message(format!(
                 "Invalid identifier: Identifier cannot start with a number! -> '{st}'"
             ))
             .with_line(lin)
             .with_column(col)
-            .with_file_path(path)
-            .with_length(st.len()));
+            .with_file(file)
+            .with_length(st.chars().count())
+            .with_help("identifiers must start with a letter or '_'")
+            .with_suggestion(Suggestion::new(
+                Span::new(lin, col, st.chars().count()),
+                &format!("_{st}"),
+                "prefix the name with '_'",
+            )));
+    }
+    // for `.lx` UAX #31: XID_Start or `_`, then XID_Continue
+    let rules = spec.identifiers();
+    Ok(rules.is_start(first_char) && st.chars().skip(1).all(|c| rules.is_continue(c)))
+}
+
+/// An ordinary `"..."` string, which has to end on the line it starts.
+///
+/// A string with `{expression}` in it is interpolated: it becomes `StringStart`, then
+/// `StringFragment`s for the text and the tokens of every expression between
+/// `InterpolationStart` and `InterpolationEnd`, then `StringEnd`. `{{` and `}}` stand for
+/// literal braces.
+fn handle_string_literal<'a>(
+    iter: &mut Chars<'a>,
+    quote: &'a AnalyzedChar,
+    file: FileId,
+    options: &TokenizeOptions,
+    interner: &mut Interner,
+    tokens: &mut Vec<AnalyzedToken>,
+) -> Result<()> {
+    let (start_line, start_col) = (quote.line, quote.column);
+    let quote_char = options.language.string_quote();
+    let mut parts: Vec<AnalyzedToken> = Vec::new();
+    let mut fragment = Fragment::new(start_line, start_col + 1);
+    let mut interpolated = false;
+    let mut last = None;
+
+    while let Some(curr) = iter.next() {
+        match &curr.kind {
+            CharKind::Char(c) if *c == quote_char => {
+                if !interpolated {
+                    // both quotes are part of the token's span
+                    tokens.push(AnalyzedToken {
+                        token: Token::StringLiteral(fragment.text),
+                        line: start_line,
+                        column: start_col,
+                        length: fragment.length + 2,
+                        soft_keyword: None,
+                    });
+                    return Ok(());
+                }
+                parts.extend(fragment.into_token());
+                tokens.push(AnalyzedToken {
+                    token: Token::StringStart,
+                    line: start_line,
+                    column: start_col,
+                    length: 1,
+                    soft_keyword: None,
+                });
+                tokens.append(&mut parts);
+                tokens.push(AnalyzedToken {
+                    token: Token::StringEnd,
+                    line: curr.line,
+                    column: curr.column,
+                    length: 1,
+                    soft_keyword: None,
+                });
+                return Ok(());
+            }
+            CharKind::Char(c @ ('{' | '}'))
+                if iter
+                    .peek()
+                    .is_some_and(|next| matches!(next.kind, CharKind::Char(n) if n == *c)) =>
+            {
+                iter.next();
+                fragment.push(*c, 2);
+            }
+            CharKind::Char('{') => {
+                interpolated = true;
+                parts.extend(fragment.into_token());
+                parts.push(AnalyzedToken {
+                    token: Token::InterpolationStart,
+                    line: curr.line,
+                    column: curr.column,
+                    length: 1,
+                    soft_keyword: None,
+                });
+                lex(iter, file, options, interner, &mut parts, Some(curr))?;
+                // the expression can hold a multi-line string, so the `}` may be lines below
+                let (line, close) = parts
+                    .last()
+                    .map_or((curr.line, curr.column), |t| (t.line, t.column));
+                fragment = Fragment::new(line, close + 1);
+            }
+            CharKind::Char(c) | CharKind::Whitespace(c) => fragment.push(*c, 1),
+            CharKind::Newline => {
+                last = Some(curr);
+                break;
+            }
+        }
+        last = Some(curr);
+    }
+    let triple = quote_char.to_string().repeat(3);
+    let mut err = unclosed_string(start_line, start_col, last, file).with_help(&format!(
+        "a string ends on the line it starts, use {triple}...{triple} for text over several lines"
+    ));
+    if !interpolated {
+        err = err.with_suggestion(Suggestion::new(
+            closing_quote(&fragment.text, start_line, start_col + 1),
+            &quote_char.to_string(),
+            "close the string on the line it starts",
+        ));
     }
-    Ok(st.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    Err(err)
 }
 
-fn is_symbol_start(c: char) -> bool {
-    SYMBOLS.contains(c.to_string().as_str())
+/// Literal text of a string between its quotes and interpolations.
+struct Fragment {
+    text: String,
+    line: usize,
+    column: usize,
+    // source chars, which is more than `text` has when braces are escaped
+    length: usize,
 }
-fn try_two_char_symbol(first: char, second: char) -> Option<String> {
-    let combined = format!("{}{}", first, second);
-    if SYMBOLS.contains(combined.as_str()) {
-        Some(combined.to_owned())
-    } else {
-        None
+
+impl Fragment {
+    fn new(line: usize, column: usize) -> Fragment {
+        Fragment {
+            text: String::new(),
+            line,
+            column,
+            length: 0,
+        }
+    }
+    fn push(&mut self, c: char, source_len: usize) {
+        self.text.push(c);
+        self.length += source_len;
+    }
+    fn into_token(self) -> Option<AnalyzedToken> {
+        (!self.text.is_empty()).then_some(AnalyzedToken {
+            token: Token::StringFragment(self.text),
+            line: self.line,
+            column: self.column,
+            length: self.length,
+            soft_keyword: None,
+        })
     }
 }
 
-fn handle_string_literal<'a, I>(
+/// L0007 for an interpolation opened at `open` that was still open at `at`.
+fn unclosed_interpolation(open: &AnalyzedChar, at: &AnalyzedChar, file: FileId) -> Error {
+    let end = match at.kind {
+        CharKind::Newline => {
+            Label::secondary(Span::new(at.line, at.column, 1)).with_message("line ended here")
+        }
+        _ => Label::secondary(end_of_file(Some(at), open.line, open.column))
+            .with_message("file ended here"),
+    };
+    Error::new(LexingError)
+        .with_code(ErrorCode::UnclosedInterpolation)
+        .with_message("Unclosed interpolation in string literal!".to_string())
+        .with_line(open.line)
+        .with_column(open.column)
+        .with_file(file)
+        .with_length(1)
+        .with_label(
+            Label::primary(Span::new(open.line, open.column, 1))
+                .with_message("interpolation started here"),
+        )
+        .with_label(end)
+        .with_help("close it with '}', or write '{{' for a literal '{'")
+}
+
+/// `r"..."` or `r#"..."#`: nothing inside is special, and the string ends at a quote
+/// followed by as many `#` as it was opened with.
+fn handle_raw_string<'a, I>(
     iter: &mut std::iter::Peekable<I>,
     start_line: usize,
     start_col: usize,
-    path: &str,
+    hashes: usize,
+    quote: char,
+    file: FileId,
 ) -> Result<AnalyzedToken>
 where
     I: std::iter::Iterator<Item = &'a AnalyzedChar>,
 {
     let mut literal_buf = String::new();
+    let mut last = None;
 
-    for curr in iter.by_ref() {
+    while let Some(curr) = iter.next() {
+        last = Some(curr);
         match &curr.kind {
-            CharKind::Char('"') => {
-                let length = literal_buf.len();
-                return Ok(AnalyzedToken {
-                    token: Token::StringLiteral(literal_buf),
-                    line: start_line,
-                    column: start_col,
-                    length: length,
-                });
-            }
-            CharKind::Char(c) => {
-                literal_buf.push(*c);
-            }
-            CharKind::Newline => {
-                literal_buf.push('\n');
+            CharKind::Char(c) if *c == quote => {
+                let mut closing = 0;
+                while closing < hashes
+                    && iter
+                        .peek()
+                        .is_some_and(|c| matches!(c.kind, CharKind::Char('#')))
+                {
+                    last = iter.next();
+                    closing += 1;
+                }
+                if closing == hashes {
+                    // `r`, both quotes and the hashes on both sides
+                    let length = literal_buf.chars().count() + 3 + 2 * hashes;
+                    return Ok(AnalyzedToken {
+                        token: Token::StringLiteral(literal_buf),
+                        line: start_line,
+                        column: start_col,
+                        length,
+                        soft_keyword: None,
+                    });
+                }
+                literal_buf.push(quote);
+                literal_buf.push_str(&"#".repeat(closing));
             }
-            CharKind::Whitespace => {
-                literal_buf.push(' ');
+            CharKind::Char(c) | CharKind::Whitespace(c) => literal_buf.push(*c),
+            CharKind::Newline => break,
+        }
+    }
+    let closing = format!("{quote}{}", "#".repeat(hashes));
+    Err(unclosed_string(start_line, start_col, last, file)
+        .with_help(&format!("this raw string ends with '{closing}'"))
+        .with_suggestion(Suggestion::new(
+            closing_quote(&literal_buf, start_line, start_col + hashes + 2),
+            &closing,
+            "close the string on the line it starts",
+        )))
+}
+
+/// `"""..."""` over several lines. A line break right after the opening quotes and the
+/// line of the closing quotes are not part of the value, and the indentation every line
+/// shares is removed.
+fn handle_multiline_string<'a, I>(
+    iter: &mut std::iter::Peekable<I>,
+    start_line: usize,
+    start_col: usize,
+    quote: char,
+    file: FileId,
+) -> Result<AnalyzedToken>
+where
+    I: std::iter::Iterator<Item = &'a AnalyzedChar>,
+{
+    // the other two opening quotes
+    iter.next();
+    let mut last = iter.next();
+    let mut raw = String::new();
+
+    while let Some(curr) = iter.next() {
+        last = Some(curr);
+        match &curr.kind {
+            CharKind::Char(c) if *c == quote => {
+                let mut quotes = 1;
+                while quotes < 3
+                    && iter
+                        .peek()
+                        .is_some_and(|c| matches!(c.kind, CharKind::Char(q) if q == quote))
+                {
+                    last = iter.next();
+                    quotes += 1;
+                }
+                if quotes == 3 {
+                    let length = raw.chars().count() + 6;
+                    return Ok(AnalyzedToken {
+                        token: Token::StringLiteral(dedent(&raw)),
+                        line: start_line,
+                        column: start_col,
+                        length,
+                        soft_keyword: None,
+                    });
+                }
+                raw.push_str(&quote.to_string().repeat(quotes));
             }
+            CharKind::Char(c) | CharKind::Whitespace(c) => raw.push(*c),
+            CharKind::Newline => raw.push('\n'),
         }
     }
-    Err(Error::new(LexingError)
+    let triple = quote.to_string().repeat(3);
+    Err(unclosed_string(start_line, start_col, last, file)
+        .with_help(&format!("a multi-line string ends with '{triple}'")))
+}
+
+/// The value of a multi-line string from the text between its quotes.
+fn dedent(raw: &str) -> String {
+    let blank = |line: &str| line.trim().is_empty();
+    let mut lines: Vec<&str> = raw.split('\n').collect();
+    // text right after the opening quotes keeps its place and has no indentation to share
+    let first = lines.remove(0);
+    let first = (lines.is_empty() || !blank(first)).then_some(first);
+    if lines.last().is_some_and(|line| blank(line)) {
+        lines.pop();
+    }
+    let indent = lines
+        .iter()
+        .filter(|line| !blank(line))
+        .map(|line| line.chars().take_while(|c| *c == ' ' || *c == '\t').count())
+        .min()
+        .unwrap_or(0);
+    let dedented = lines
+        .iter()
+        .map(|line| match line.char_indices().nth(indent) {
+            _ if blank(line) => "",
+            Some((i, _)) => &line[i..],
+            None => "",
+        });
+    first
+        .into_iter()
+        .chain(dedented)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The number of `#` if the word before a quote is a raw string prefix like `r` or `r##`.
+fn raw_prefix(buf: &[&AnalyzedChar]) -> Option<usize> {
+    let (first, hashes) = buf.split_first()?;
+    if !matches!(first.kind, CharKind::Char('r')) {
+        return None;
+    }
+    hashes
+        .iter()
+        .all(|c| matches!(c.kind, CharKind::Char('#')))
+        .then_some(hashes.len())
+}
+
+/// L0001 for a string opened at `line`/`col` that was still open at `last`.
+fn unclosed_string(line: usize, col: usize, last: Option<&AnalyzedChar>, file: FileId) -> Error {
+    let end = match last {
+        Some(c) if matches!(c.kind, CharKind::Newline) => {
+            Label::secondary(Span::new(c.line, c.column, 1)).with_message("line ended here")
+        }
+        _ => Label::secondary(end_of_file(last, line, col)).with_message("file ended here"),
+    };
+    Error::new(LexingError)
+        .with_code(ErrorCode::UnclosedString)
         .with_message("Unclosed string literal!".to_string())
-        .with_line(start_line)
-        .with_column(start_col)
-        .with_file_path(path))
+        .with_line(line)
+        .with_column(col)
+        .with_file(file)
+        .with_length(1)
+        .with_label(Label::primary(Span::new(line, col, 1)).with_message("string started here"))
+        .with_label(end)
+}
+
+/// Where an unclosed string most likely ends: the end of the line its text starts on at
+/// `col`, before a trailing `;` or `)`.
+fn closing_quote(literal: &str, line: usize, col: usize) -> Span {
+    let first_line = literal.split('\n').next().unwrap_or("").trim_end();
+    let body = first_line.trim_end_matches([';', ')']).trim_end();
+    Span::new(line, col + body.chars().count(), 0)
 }
 
 fn handle_char_literal<'a, I>(
     iter: &mut std::iter::Peekable<I>,
     start_line: usize,
     start_col: usize,
-    path: &str,
+    quote: char,
+    file: FileId,
 ) -> Result<AnalyzedToken>
 where
     I: Iterator<Item = &'a AnalyzedChar>,
 {
-    let val: char;
+    let unclosed = |message: &str| {
+        Error::new(LexingError)
+            .with_code(ErrorCode::UnclosedChar)
+            .with_message(message.to_string())
+            .with_line(start_line)
+            .with_column(start_col)
+            .with_file(file)
+            .with_label(
+                Label::primary(Span::new(start_line, start_col, 1))
+                    .with_message("char literal started here"),
+            )
+    };
 
-    match iter.next() {
+    let val = match iter.next() {
         Some(analyzed) => match analyzed.kind {
-            CharKind::Char(c) => val = c,
-            CharKind::Whitespace => val = ' ',
-            CharKind::Newline => val = '\n',
+            CharKind::Char(c) => c,
+            CharKind::Whitespace(c) => c,
+            CharKind::Newline => {
+                return Err(unclosed("Unclosed char literal!")
+                    .with_length(1)
+                    .with_label(
+                        Label::secondary(Span::new(analyzed.line, analyzed.column, 1))
+                            .with_message("line ended here"),
+                    ));
+            }
         },
         None => {
-            return Err(Error::new(LexingError)
-                .with_message("Unexpected character after single quote!".to_string())
-                .with_line(start_line)
-                .with_column(start_col)
-                .with_file_path(path));
+            return Err(unclosed("Unexpected character after single quote!")
+                .with_length(1)
+                .with_label(
+                    Label::secondary(end_of_file(None, start_line, start_col))
+                        .with_message("file ended here"),
+                ));
         }
     };
-    match iter.next() {
-        Some(analyzed) => match analyzed.kind {
-            CharKind::Char('\'') => Ok(AnalyzedToken {
-                token: Token::CharLiteral(val),
-                line: start_line,
-                column: start_col,
-                length: 1,
-            }),
-            _ => Err(Error::new(LexingError)
-                .with_message("Expected closing single quote!".to_string())
-                .with_line(start_line)
-                .with_column(start_col)
-                .with_file_path(path)),
-        },
-        None => Err(Error::new(LexingError)
-            .with_message("Unexpected EOF while parsing char literal!".to_string())
+
+    // the chars after the first one, up to a closing quote on the same line
+    let mut extra: Option<&AnalyzedChar> = None;
+    let mut last: Option<&AnalyzedChar> = None;
+    let mut length = 2;
+    loop {
+        let Some(analyzed) = iter.next() else {
+            return Err(unclosed("Unexpected EOF while parsing char literal!")
+                .with_length(2)
+                .with_label(
+                    Label::secondary(end_of_file(last, start_line, start_col + 1))
+                        .with_message("file ended here"),
+                ));
+        };
+        match analyzed.kind {
+            CharKind::Char(c) if c == quote => break,
+            CharKind::Newline => {
+                return Err(unclosed("Unclosed char literal!")
+                    .with_length(2)
+                    .with_label(
+                        Label::secondary(Span::new(analyzed.line, analyzed.column, 1))
+                            .with_message("line ended here"),
+                    ));
+            }
+            _ => {
+                // only the first of the extra chars is pointed at
+                extra = extra.or(Some(analyzed));
+                last = Some(analyzed);
+                length += 1;
+            }
+        }
+    }
+
+    match extra {
+        None => Ok(AnalyzedToken {
+            token: Token::CharLiteral(val),
+            line: start_line,
+            column: start_col,
+            length: 3,
+            soft_keyword: None,
+        }),
+        Some(extra) => Err(Error::new(LexingError)
+            .with_code(ErrorCode::MultiCharLiteral)
+            .with_message("Char literal holds more than one character!".to_string())
             .with_line(start_line)
             .with_column(start_col)
-            .with_file_path(path)),
+            .with_file(file)
+            .with_length(length + 1)
+            .with_label(
+                Label::primary(Span::new(start_line, start_col, length + 1))
+                    .with_message("more than one character"),
+            )
+            .with_label(
+                Label::secondary(Span::new(extra.line, extra.column, 1))
+                    .with_message(&format!("expected `{quote}` here")),
+            )
+            .with_help("a char literal holds exactly one character, use \"...\" for text")),
+    }
+}
+
+/// The position just past the last char of the file, for "file ended here" labels.
+fn end_of_file(last: Option<&AnalyzedChar>, line: usize, col: usize) -> Span {
+    match last {
+        // a trailing newline ends the line it is on, so point at it rather than past it
+        Some(c) if matches!(c.kind, CharKind::Newline) => Span::new(c.line, c.column, 1),
+        Some(c) => Span::new(c.line, c.column + 1, 1),
+        None => Span::new(line, col + 1, 1),
     }
 }
 
@@ -298,7 +879,9 @@ fn char_buf_clear(
     ch_buf: &mut Vec<&AnalyzedChar>,
     st_buf: &mut String,
     res_vec: &mut Vec<AnalyzedToken>,
-    path: &str,
+    file: FileId,
+    spec: &LanguageSpec,
+    interner: &mut Interner,
 ) -> Result<()> {
     if !ch_buf.is_empty() {
         st_buf.clear();
@@ -310,20 +893,33 @@ fn char_buf_clear(
         let b_lin = ch_buf[0].line;
         let b_col = ch_buf[0].column;
         ch_buf.clear();
-        final_buffer(res_vec, st_buf, b_lin, b_col, path)?;
+        final_buffer(res_vec, st_buf, b_lin, b_col, file, spec, interner)?;
     }
     Ok(())
 }
 
-fn handle_command_line<'a, I>(iter: &mut std::iter::Peekable<I>) -> Result<()>
-where
-    I: Iterator<Item = &'a AnalyzedChar>,
-{
+/// A comment from its marker at `start` to the end of the line.
+fn handle_comment(
+    iter: &mut Chars<'_>,
+    start: &AnalyzedChar,
+    spec: &LanguageSpec,
+) -> AnalyzedToken {
+    let mut comment_buf = String::new();
+    if let CharKind::Char(c) = start.kind {
+        comment_buf.push(c);
+    }
     for c in iter.by_ref() {
         match c.kind {
-            CharKind::Newline => return Ok(()),
-            _ => continue,
+            CharKind::Newline => break,
+            CharKind::Char(ch) => comment_buf.push(ch),
+            CharKind::Whitespace(ch) => comment_buf.push(ch),
         }
     }
-    Ok(())
+    AnalyzedToken {
+        length: comment_buf.chars().count(),
+        token: spec.comment(&comment_buf),
+        line: start.line,
+        column: start.column,
+        soft_keyword: None,
+    }
 }
@@ -1,28 +1,38 @@
 // calisma mantigini kavra
 use once_cell::sync::Lazy;
 use std::collections::HashSet;
+use std::fmt;
 
-// pub static KEYWORDS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
-//     HashSet::from([
-//         "let", "op", "if", "else", "while", "for", "loop", "success", "i32", "str", "string",
-//         "char", "f32", "bool", "break", "continue", "nret", "data", "and", "or",
-//     ])
-// });
-
-pub static SYMBOLS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
-    HashSet::from([
-        "=", "\'", "\"", ":", ";", "::", ")", "(", "{", "}", "-", "+", "/", "*", "->", "?", "-<",
-        "<-", "-!", ">", ">", "%", "[", "]", "^", "//", "/*", "==", "<=", ">=", "<", ">", ",",
-    ])
-});
-
-#[derive(Debug, Clone)]
+use super::interner::Symbol;
+use super::language::LX;
+use super::position::{Position, PositionEncoding};
+use super::source_map::SourceFile;
+
+/// Keywords of the built-in `.lx` language. Lexing and linting go by their `LanguageSpec`.
+pub static KEYWORDS: Lazy<HashSet<&'static str>> = Lazy::new(|| LX.keywords().collect());
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
-    Identifier(String),
+    Identifier(Symbol),
     Number(i32),
-    Symbol(String),
+    Symbol(Symbol),
     StringLiteral(String),
+    /// The opening quote of an interpolated string.
+    StringStart,
+    /// Literal text of an interpolated string, with `{{` and `}}` decoded.
+    StringFragment(String),
+    /// `{` opening an expression inside an interpolated string.
+    InterpolationStart,
+    /// `}` closing an expression inside an interpolated string.
+    InterpolationEnd,
+    /// The closing quote of an interpolated string.
+    StringEnd,
     CharLiteral(char),
+    Comment(String),
+    /// `///` comment documenting the `op`, `data` or `let` item that follows it.
+    DocComment(String),
+    /// `//!` comment documenting the file it is in.
+    ModuleDocComment(String),
     RBrace,
     LBrace,
     Semicolon,
@@ -61,10 +71,11 @@ pub enum Token {
     For,
     Loop,
     Success,
-    IntType(String),
+    IntType(IntType),
+    /// `string`, or its short form `str`.
     String,
     Char,
-    FloatType(String),
+    FloatType(FloatType),
     Bool,
     True,
     False,
@@ -74,13 +85,395 @@ pub enum Token {
     Data,
     And,
     Or,
+    /// The end of the file, just past its last char. Only `TokenCursor` produces it.
+    Eof,
 }
 
+/// A `Token` without its payload, for asking whether the next token is any identifier,
+/// number or integer type rather than one in particular.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Identifier,
+    Number,
+    Symbol,
+    StringLiteral,
+    StringStart,
+    StringFragment,
+    InterpolationStart,
+    InterpolationEnd,
+    StringEnd,
+    CharLiteral,
+    Comment,
+    DocComment,
+    ModuleDocComment,
+    RBrace,
+    LBrace,
+    Semicolon,
+    Colon,
+    Equals,
+    EqualEqual,
+    NotEquals,
+    DoubleColon,
+    LessEqual,
+    GreaterEqual,
+    LeftParen,
+    RightParen,
+    Minus,
+    Not,
+    Plus,
+    Slash,
+    Star,
+    Question,
+    Percent,
+    LBracket,
+    RBracket,
+    Power,
+    Arrow,
+    ReverseArrow,
+    ChainNot,
+    ChainBack,
+    Less,
+    Greater,
+    Unknown,
+    Let,
+    Op,
+    If,
+    ElseIf,
+    Else,
+    While,
+    For,
+    Loop,
+    Success,
+    IntType,
+    String,
+    Char,
+    FloatType,
+    Bool,
+    True,
+    False,
+    Break,
+    Continue,
+    Nret,
+    Data,
+    And,
+    Or,
+    Eof,
+}
+
+impl TokenKind {
+    /// How tokens of this kind are written in `.lx`, if they are always written the same way.
+    pub fn spelling(self) -> Option<&'static str> {
+        let spelling = match self {
+            TokenKind::RBrace => "}",
+            TokenKind::LBrace => "{",
+            TokenKind::Semicolon => ";",
+            TokenKind::Colon => ":",
+            TokenKind::Equals => "=",
+            TokenKind::EqualEqual => "==",
+            TokenKind::NotEquals => "!=",
+            TokenKind::DoubleColon => "::",
+            TokenKind::LessEqual => "<=",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::LeftParen => "(",
+            TokenKind::RightParen => ")",
+            TokenKind::Minus => "-",
+            TokenKind::Not => "!",
+            TokenKind::Plus => "+",
+            TokenKind::Slash => "/",
+            TokenKind::Star => "*",
+            TokenKind::Question => "?",
+            TokenKind::Percent => "%",
+            TokenKind::LBracket => "[",
+            TokenKind::RBracket => "]",
+            TokenKind::Power => "^",
+            TokenKind::Arrow => "->",
+            TokenKind::ReverseArrow => "<-",
+            TokenKind::ChainNot => "-!",
+            TokenKind::ChainBack => "-<",
+            TokenKind::Less => "<",
+            TokenKind::Greater => ">",
+            TokenKind::Let => "let",
+            TokenKind::Op => "op",
+            TokenKind::If => "if",
+            TokenKind::ElseIf => "else if",
+            TokenKind::Else => "else",
+            TokenKind::While => "while",
+            TokenKind::For => "for",
+            TokenKind::Loop => "loop",
+            TokenKind::Success => "success",
+            TokenKind::String => "string",
+            TokenKind::Char => "char",
+            TokenKind::Bool => "bool",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::Nret => "nret",
+            TokenKind::Data => "data",
+            TokenKind::And => "and",
+            TokenKind::Or => "or",
+            TokenKind::Identifier
+            | TokenKind::Number
+            | TokenKind::Symbol
+            | TokenKind::StringLiteral
+            | TokenKind::StringStart
+            | TokenKind::StringFragment
+            | TokenKind::InterpolationStart
+            | TokenKind::InterpolationEnd
+            | TokenKind::StringEnd
+            | TokenKind::CharLiteral
+            | TokenKind::Comment
+            | TokenKind::DocComment
+            | TokenKind::ModuleDocComment
+            | TokenKind::Unknown
+            | TokenKind::IntType
+            | TokenKind::FloatType
+            | TokenKind::Eof => return None,
+        };
+        Some(spelling)
+    }
+}
+
+/// The kind the way an error message names it: `` `let` ``, `identifier`.
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(spelling) = self.spelling() {
+            return write!(f, "`{spelling}`");
+        }
+        let name = match self {
+            TokenKind::Identifier => "identifier",
+            TokenKind::Number => "number",
+            TokenKind::Symbol => "operator",
+            TokenKind::StringLiteral => "string",
+            TokenKind::StringStart => "start of a string",
+            TokenKind::StringFragment => "string text",
+            TokenKind::InterpolationStart => "`{` of an interpolation",
+            TokenKind::InterpolationEnd => "`}` of an interpolation",
+            TokenKind::StringEnd => "end of a string",
+            TokenKind::CharLiteral => "char",
+            TokenKind::Comment | TokenKind::DocComment | TokenKind::ModuleDocComment => "comment",
+            TokenKind::IntType => "integer type",
+            TokenKind::FloatType => "float type",
+            TokenKind::Eof => "end of file",
+            _ => "unknown token",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How many bits an integer type has; `Size` is the width of a pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntWidth {
+    W8,
+    W16,
+    W32,
+    W64,
+    W128,
+    Size,
+}
+
+/// An integer type keyword:
+///
+/// | Spelling | Token |
+/// | --- | --- |
+/// | `i8`, `i16`, `i32`, `i64`, `i128` | `IntType { signed: true, width: W8 .. W128 }` |
+/// | `u8`, `u16`, `u32`, `u64`, `u128` | `IntType { signed: false, width: W8 .. W128 }` |
+/// | `isize`, `usize` | `IntType { signed: true / false, width: Size }` |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IntType {
+    pub signed: bool,
+    pub width: IntWidth,
+}
+
+impl IntType {
+    /// The type spelled `name` in `.lx`, e.g. `u8` or `isize`.
+    pub fn from_name(name: &str) -> Option<IntType> {
+        let signed = match name.chars().next()? {
+            'i' => true,
+            'u' => false,
+            _ => return None,
+        };
+        let width = match &name[1..] {
+            "8" => IntWidth::W8,
+            "16" => IntWidth::W16,
+            "32" => IntWidth::W32,
+            "64" => IntWidth::W64,
+            "128" => IntWidth::W128,
+            "size" => IntWidth::Size,
+            _ => return None,
+        };
+        Some(IntType { signed, width })
+    }
+
+    /// The `.lx` spelling of the type.
+    pub fn name(&self) -> &'static str {
+        match (self.signed, self.width) {
+            (true, IntWidth::W8) => "i8",
+            (true, IntWidth::W16) => "i16",
+            (true, IntWidth::W32) => "i32",
+            (true, IntWidth::W64) => "i64",
+            (true, IntWidth::W128) => "i128",
+            (true, IntWidth::Size) => "isize",
+            (false, IntWidth::W8) => "u8",
+            (false, IntWidth::W16) => "u16",
+            (false, IntWidth::W32) => "u32",
+            (false, IntWidth::W64) => "u64",
+            (false, IntWidth::W128) => "u128",
+            (false, IntWidth::Size) => "usize",
+        }
+    }
+
+    /// Every integer type, signed ones first.
+    pub fn all() -> impl Iterator<Item = IntType> {
+        use IntWidth::*;
+        [true, false].into_iter().flat_map(|signed| {
+            [W8, W16, W32, W64, W128, Size]
+                .into_iter()
+                .map(move |width| IntType { signed, width })
+        })
+    }
+}
+
+/// A float type keyword: `f32` is `F32` and `f64` is `F64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatType {
+    F32,
+    F64,
+}
+
+impl FloatType {
+    pub const ALL: [FloatType; 2] = [FloatType::F32, FloatType::F64];
+
+    pub fn from_name(name: &str) -> Option<FloatType> {
+        match name {
+            "f32" => Some(FloatType::F32),
+            "f64" => Some(FloatType::F64),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FloatType::F32 => "f32",
+            FloatType::F64 => "f64",
+        }
+    }
+}
+
+impl Token {
+    /// The kind of the token, without its payload.
+    pub fn kind(&self) -> TokenKind {
+        match self {
+            Token::Identifier(_) => TokenKind::Identifier,
+            Token::Number(_) => TokenKind::Number,
+            Token::Symbol(_) => TokenKind::Symbol,
+            Token::StringLiteral(_) => TokenKind::StringLiteral,
+            Token::StringStart => TokenKind::StringStart,
+            Token::StringFragment(_) => TokenKind::StringFragment,
+            Token::InterpolationStart => TokenKind::InterpolationStart,
+            Token::InterpolationEnd => TokenKind::InterpolationEnd,
+            Token::StringEnd => TokenKind::StringEnd,
+            Token::CharLiteral(_) => TokenKind::CharLiteral,
+            Token::Comment(_) => TokenKind::Comment,
+            Token::DocComment(_) => TokenKind::DocComment,
+            Token::ModuleDocComment(_) => TokenKind::ModuleDocComment,
+            Token::RBrace => TokenKind::RBrace,
+            Token::LBrace => TokenKind::LBrace,
+            Token::Semicolon => TokenKind::Semicolon,
+            Token::Colon => TokenKind::Colon,
+            Token::Equals => TokenKind::Equals,
+            Token::EqualEqual => TokenKind::EqualEqual,
+            Token::NotEquals => TokenKind::NotEquals,
+            Token::DoubleColon => TokenKind::DoubleColon,
+            Token::LessEqual => TokenKind::LessEqual,
+            Token::GreaterEqual => TokenKind::GreaterEqual,
+            Token::LeftParen => TokenKind::LeftParen,
+            Token::RightParen => TokenKind::RightParen,
+            Token::Minus => TokenKind::Minus,
+            Token::Not => TokenKind::Not,
+            Token::Plus => TokenKind::Plus,
+            Token::Slash => TokenKind::Slash,
+            Token::Star => TokenKind::Star,
+            Token::Question => TokenKind::Question,
+            Token::Percent => TokenKind::Percent,
+            Token::LBracket => TokenKind::LBracket,
+            Token::RBracket => TokenKind::RBracket,
+            Token::Power => TokenKind::Power,
+            Token::Arrow => TokenKind::Arrow,
+            Token::ReverseArrow => TokenKind::ReverseArrow,
+            Token::ChainNot => TokenKind::ChainNot,
+            Token::ChainBack => TokenKind::ChainBack,
+            Token::Less => TokenKind::Less,
+            Token::Greater => TokenKind::Greater,
+            Token::Unknown => TokenKind::Unknown,
+            Token::Let => TokenKind::Let,
+            Token::Op => TokenKind::Op,
+            Token::If => TokenKind::If,
+            Token::ElseIf => TokenKind::ElseIf,
+            Token::Else => TokenKind::Else,
+            Token::While => TokenKind::While,
+            Token::For => TokenKind::For,
+            Token::Loop => TokenKind::Loop,
+            Token::Success => TokenKind::Success,
+            Token::IntType(_) => TokenKind::IntType,
+            Token::String => TokenKind::String,
+            Token::Char => TokenKind::Char,
+            Token::FloatType(_) => TokenKind::FloatType,
+            Token::Bool => TokenKind::Bool,
+            Token::True => TokenKind::True,
+            Token::False => TokenKind::False,
+            Token::Break => TokenKind::Break,
+            Token::Continue => TokenKind::Continue,
+            Token::Nret => TokenKind::Nret,
+            Token::Data => TokenKind::Data,
+            Token::And => TokenKind::And,
+            Token::Or => TokenKind::Or,
+            Token::Eof => TokenKind::Eof,
+        }
+    }
+
+    /// How the token is written in `.lx`, for the tokens that are always written the same
+    /// way: keywords, types and operators. `String` is spelled `string`, never `str`.
+    pub fn spelling(&self) -> Option<&'static str> {
+        match self {
+            Token::IntType(ty) => Some(ty.name()),
+            Token::FloatType(ty) => Some(ty.name()),
+            token => token.kind().spelling(),
+        }
+    }
+
+    /// Any kind of comment, documentation included.
+    pub fn is_comment(&self) -> bool {
+        matches!(
+            self,
+            Token::Comment(_) | Token::DocComment(_) | Token::ModuleDocComment(_)
+        )
+    }
+}
+
+#[derive(Clone)]
 pub struct AnalyzedToken {
     pub token: Token,
     pub line: usize,
     pub column: usize,
     pub length: usize,
+    /// For an identifier spelled like a contextual keyword, the keyword it is where the
+    /// parser expects one, e.g. `Data` for `data`.
+    pub soft_keyword: Option<Token>,
+}
+
+impl AnalyzedToken {
+    /// Where the token starts in `source`, with the column counted in `encoding`.
+    pub fn start(&self, source: &SourceFile, encoding: PositionEncoding) -> Option<Position> {
+        let column = source.encode_column(self.line, self.column, encoding)?;
+        Some(Position::new(self.line, column))
+    }
+    /// The position just past the token's last char, which may be on a later line.
+    pub fn end(&self, source: &SourceFile, encoding: PositionEncoding) -> Option<Position> {
+        let end = source.advance(self.line, self.column, self.length)?;
+        let column = source.encode_column(end.line, end.column, encoding)?;
+        Some(Position::new(end.line, column))
+    }
 }
 
 impl std::fmt::Debug for AnalyzedToken {
@@ -90,6 +483,9 @@ impl std::fmt::Debug for AnalyzedToken {
             "AnalyzedToken -> \x1b[033;1m{:?}\x1b[0m [line: {}, column: {}, length: {}]",
             self.token, self.line, self.column, self.length
         )?;
+        if let Some(keyword) = &self.soft_keyword {
+            write!(f, " (could be {keyword:?})")?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::PathBuf;
+
+use lexer::error_handling::ErrorCode;
+use lexer::modules::{
+    batch::{BatchOptions, lex_files, lex_files_with, source_files},
+    source_map::SourceMap,
+};
+
+/// A fresh directory under the system temp dir with `files` written into it.
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("lexer-batch-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, text) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+    root
+}
+
+#[test]
+fn directories_expand_to_their_sorted_source_files() {
+    let root = tree(
+        "expand",
+        &[
+            ("b.lx", "let b;"),
+            ("a/z.lx", "let z;"),
+            ("a/y.lx", "let y;"),
+            ("notes.txt", "not lexed"),
+        ],
+    );
+    let dir = root.display().to_string();
+    let single = root.join("b.lx").display().to_string();
+    let files = source_files(&[single.clone(), dir], "lx").unwrap();
+    let names: Vec<String> = files
+        .iter()
+        .map(|f| {
+            f.strip_prefix(&*root.display().to_string())
+                .unwrap()
+                .replace('\\', "/")
+        })
+        .collect();
+    assert_eq!(names, ["/b.lx", "/a/y.lx", "/a/z.lx", "/b.lx"]);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn results_keep_the_input_order_whatever_the_threads() {
+    let files: Vec<(String, String)> = (0..40)
+        .map(|i| {
+            let text = match i % 7 {
+                // every seventh file does not lex
+                3 => format!("let s{i} = \"open;\n"),
+                _ => format!("let x{i} = {i};\n").repeat(i + 1),
+            };
+            (format!("f{i:02}.lx"), text)
+        })
+        .collect();
+    let borrowed: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(p, t)| (p.as_str(), t.as_str()))
+        .collect();
+    let root = tree("order", &borrowed);
+    let mut paths = source_files(&[root.display().to_string()], "lx").unwrap();
+    paths.push(root.join("missing.lx").display().to_string());
+
+    let describe = |jobs: usize| {
+        let mut sources = SourceMap::new();
+        let batch =
+            lex_files_with(&mut sources, &paths, &BatchOptions::new().with_jobs(jobs)).unwrap();
+        let outcomes: Vec<String> = batch
+            .files
+            .iter()
+            .map(|f| match &f.result {
+                Ok(tokens) => {
+                    // files are added to the source map in input order
+                    let file = tokens.file().unwrap();
+                    assert_eq!(sources.get(file).name(), f.path);
+                    format!("{} tokens", tokens.len())
+                }
+                Err(err) => format!("{:?}", err.code()),
+            })
+            .collect();
+        (outcomes, batch.summary)
+    };
+
+    let (one, one_summary) = describe(1);
+    let (four, four_summary) = describe(4);
+    assert_eq!(one, four);
+    assert_eq!(one.len(), 41);
+    assert_eq!(one[0], "5 tokens");
+    assert_eq!(one[3], "Some(UnclosedString)");
+    assert_eq!(one[40], "Some(Io)");
+
+    assert_eq!(one_summary.files, 41);
+    assert_eq!(one_summary.errors, 7);
+    assert_eq!(four_summary.tokens, one_summary.tokens);
+    let bytes: usize = files.iter().map(|(_, t)| t.len()).sum();
+    assert_eq!(one_summary.bytes, bytes);
+    assert!(one_summary.bytes_per_second() > 0.0);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn the_errors_of_a_batch_are_listed_in_input_order() {
+    let root = tree(
+        "errors",
+        &[
+            ("a.lx", "let c = 'ab';"),
+            ("b.lx", "let ok;"),
+            ("c.lx", "let 1x;"),
+        ],
+    );
+    let paths = source_files(&[root.display().to_string()], "lx").unwrap();
+    let mut sources = SourceMap::new();
+    let batch = lex_files(&mut sources, &paths).unwrap();
+    let codes: Vec<Option<ErrorCode>> = batch.errors().map(|e| e.code()).collect();
+    assert_eq!(
+        codes,
+        [
+            Some(ErrorCode::MultiCharLiteral),
+            Some(ErrorCode::IdentifierStartsWithDigit)
+        ]
+    );
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,198 @@
+use lexer::error_handling::{ErrorCode, Result};
+use lexer::modules::{
+    analyser::analyze_chars,
+    source_map::SourceMap,
+    token_stream::TokenStream,
+    tokenizer::{TokenizeOptions, tokenize_with},
+    tokens::{IntType, IntWidth, Token, TokenKind},
+};
+
+fn lex(source: &str) -> TokenStream {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    let options = TokenizeOptions::new().with_comments(true);
+    tokenize_with(&analyze_chars(source), file, &options).unwrap()
+}
+
+const I32: Token = Token::IntType(IntType {
+    signed: true,
+    width: IntWidth::W32,
+});
+
+#[test]
+fn the_cursor_looks_ahead_and_consumes_what_it_expects() {
+    let tokens = lex("let x: i32 = 1; // one\n/// two\nlet y");
+    let mut cursor = tokens.cursor();
+
+    assert!(cursor.at(TokenKind::Let));
+    assert!(
+        matches!(&cursor.peek_nth(1).token, Token::Identifier(name) if tokens.interner().resolve(*name) == "x")
+    );
+    assert!(!cursor.eat(TokenKind::Op));
+    assert!(cursor.eat(TokenKind::Let));
+    assert!(matches!(cursor.bump().token, Token::Identifier(_)));
+    cursor.expect(TokenKind::Colon).unwrap();
+    assert!(cursor.at_token(&I32));
+    assert!(cursor.eat_token(&I32));
+    assert_eq!(cursor.peek_nth(2).token, Token::Semicolon);
+
+    // comments and doc comments are skipped
+    for _ in 0..3 {
+        cursor.bump();
+    }
+    assert_eq!(cursor.peek().token, Token::Let);
+    assert_eq!((cursor.peek().line, cursor.peek().column), (3, 1));
+}
+
+#[test]
+fn checkpoints_rewind_the_cursor() {
+    let tokens = lex("a b c");
+    let mut cursor = tokens.cursor();
+    cursor.bump();
+    let checkpoint = cursor.checkpoint();
+    cursor.bump();
+    cursor.bump();
+    assert!(cursor.at_eof());
+    cursor.rewind(checkpoint);
+    assert!(
+        matches!(&cursor.bump().token, Token::Identifier(name) if tokens.interner().resolve(*name) == "b")
+    );
+}
+
+#[test]
+fn the_end_of_the_file_is_an_eof_token_with_a_position() {
+    let tokens = lex("let x\n");
+    let mut cursor = tokens.cursor();
+    cursor.bump();
+    cursor.bump();
+    for _ in 0..2 {
+        let eof = cursor.bump();
+        assert_eq!(eof.token, Token::Eof);
+        assert_eq!((eof.line, eof.column, eof.length), (2, 1, 0));
+    }
+    assert_eq!(cursor.peek_nth(5).token, Token::Eof);
+
+    let empty = lex("");
+    assert_eq!((empty.eof().line, empty.eof().column), (1, 1));
+    // a token spanning lines ends where its last char is
+    let multiline = lex("\"\"\"\na\n\"\"\"");
+    let eof = multiline.cursor().peek_nth(1);
+    assert_eq!((eof.line, eof.column), (3, 4));
+}
+
+#[test]
+fn expect_reports_the_token_it_found() {
+    let tokens = lex("let x: i32 = 1\nlet y");
+    let mut cursor = tokens.cursor();
+    for _ in 0..6 {
+        cursor.bump();
+    }
+    let err = cursor.expect(TokenKind::Semicolon).err().unwrap();
+    assert_eq!(err.code(), Some(ErrorCode::UnexpectedToken));
+    assert!(format!("{err:?}").contains("ParsingError"));
+    assert_eq!((err.line(), err.column()), (Some(2), Some(1)));
+    assert_eq!(
+        err.message(),
+        "Unexpected token! -> expected `;`, found `let`"
+    );
+    // the failed expect consumed nothing
+    assert!(cursor.at(TokenKind::Let));
+
+    cursor.bump();
+    cursor.bump();
+    let err = cursor.expect(TokenKind::Colon).err().unwrap();
+    assert_eq!((err.line(), err.column()), (Some(2), Some(6)));
+    assert!(err.message().ends_with("found end of file"));
+}
+
+#[test]
+fn expect_names_an_unexpected_identifier_by_its_text() {
+    let tokens = lex("let count = 1;");
+    let mut cursor = tokens.cursor();
+    cursor.bump();
+    let err = cursor.expect(TokenKind::IntType).err().unwrap();
+    assert_eq!(
+        err.message(),
+        "Unexpected token! -> expected integer type, found identifier `count`"
+    );
+}
+
+#[test]
+fn kinds_match_any_payload() {
+    let tokens = lex("let total = 42; let s = \"hi\" + 'c';");
+    let mut cursor = tokens.cursor();
+    cursor.expect(TokenKind::Let).unwrap();
+    // whatever the name or the value
+    let name = cursor.expect(TokenKind::Identifier).unwrap();
+    assert_eq!((name.line, name.column), (1, 5));
+    cursor.expect(TokenKind::Equals).unwrap();
+    assert!(cursor.at(TokenKind::Number));
+    assert!(!cursor.at_token(&Token::Number(7)));
+    assert_eq!(cursor.bump().token, Token::Number(42));
+    for kind in [
+        TokenKind::Semicolon,
+        TokenKind::Let,
+        TokenKind::Identifier,
+        TokenKind::Equals,
+        TokenKind::StringLiteral,
+        TokenKind::Plus,
+        TokenKind::CharLiteral,
+        TokenKind::Semicolon,
+    ] {
+        assert!(cursor.eat(kind), "{kind:?}");
+    }
+    assert!(cursor.at(TokenKind::Eof));
+
+    let err = lex("let 5")
+        .cursor()
+        .expect(TokenKind::Identifier)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.message(),
+        "Unexpected token! -> expected identifier, found `let`"
+    );
+}
+
+/// The errors a parser of `let name: type = value;` reports for `source`.
+fn let_statements(source: &str) -> Result<()> {
+    let tokens = lex(source);
+    let mut cursor = tokens.cursor();
+    while !cursor.at_eof() {
+        for kind in [
+            TokenKind::Let,
+            TokenKind::Identifier,
+            TokenKind::Colon,
+            TokenKind::IntType,
+            TokenKind::Equals,
+            TokenKind::Number,
+            TokenKind::Semicolon,
+        ] {
+            cursor.expect(kind)?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn the_explanation_shows_the_messages_the_parser_reports() {
+    let explanation = ErrorCode::UnexpectedToken.explanation();
+    let example = explanation
+        .split("Erroneous example:\n\n")
+        .nth(1)
+        .and_then(|rest| rest.split("\n\n").next())
+        .unwrap()
+        .replace("    ", "");
+    assert_eq!(example, "let x: i32 = 1\nlet y: i32 = 2;");
+
+    for source in [example.as_str(), example.lines().next().unwrap()] {
+        let err = let_statements(source).err().unwrap();
+        assert_eq!(err.code(), Some(ErrorCode::UnexpectedToken));
+        assert!(
+            explanation.contains(&format!("\n    {}\n", err.message())),
+            "{}",
+            err.message()
+        );
+    }
+    let_statements("let x: i32 = 1;\nlet y: i32 = 2;").unwrap();
+}
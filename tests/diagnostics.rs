@@ -0,0 +1,77 @@
+use std::error::Error as StdError;
+use std::io;
+
+use lexer::error_handling::{Error, ErrorType, to_json, to_sarif};
+use lexer::modules::{
+    analyser::analyze_chars, position::PositionEncoding, source_map::SourceMap, tokenizer::tokenize,
+};
+
+#[test]
+fn errors_render_from_memory_without_printing() {
+    let mut sources = SourceMap::new();
+    let file = sources.add("<stdin>", "let s: string = \"abc;\n".to_string());
+    let chars = analyze_chars(sources.get(file).text());
+    let err = tokenize(&chars, file).err().unwrap();
+
+    let rendered = err.render(&sources);
+    assert!(rendered.starts_with("error[L0001]: Unclosed string literal!\n"));
+    assert!(rendered.contains(" --> <stdin>:1:17\n"));
+    assert!(rendered.contains("1 | let s: string = \"abc;\n"));
+    assert!(rendered.contains("  |                 ^ string started here\n"));
+    assert!(!rendered.contains('\x1b'));
+
+    let debug = format!("{err:?}");
+    assert!(debug.starts_with("Error { type: LexingError, severity: Error, code: Some(UnclosedString), message: \"Unclosed string literal!\""));
+    assert_eq!(
+        err.to_string(),
+        "LexingError, Unclosed string literal! at line 1, column 17"
+    );
+}
+
+#[test]
+fn errors_serialize_to_json_and_sarif() {
+    let mut sources = SourceMap::new();
+    let file = sources.add("main.lx", "let c: char = 'ab';\n".to_string());
+    let chars = analyze_chars(sources.get(file).text());
+    let err = tokenize(&chars, file).err().unwrap();
+
+    let json = to_json(&err, &sources, PositionEncoding::default());
+    assert_eq!(json["code"], "L0003");
+    assert_eq!(json["severity"], "error");
+    assert_eq!(json["file"], "main.lx");
+    assert_eq!(
+        (json["line"].clone(), json["column"].clone()),
+        (1.into(), 15.into())
+    );
+    assert_eq!(json["labels"][1]["message"], "expected `'` here");
+
+    let io_err: Error = std::io::Error::from(std::io::ErrorKind::NotFound).into();
+    let sarif = to_sarif(&[err, io_err], &sources, PositionEncoding::default());
+    let run = &sarif["runs"][0];
+    assert_eq!(sarif["version"], "2.1.0");
+    assert_eq!(run["results"][0]["ruleId"], "L0003");
+    assert_eq!(
+        run["results"][0]["locations"][0]["physicalLocation"]["region"]["startColumn"],
+        15
+    );
+    assert_eq!(run["results"][1]["ruleId"], "E0001");
+    let rule_index = run["results"][1]["ruleIndex"].as_u64().unwrap() as usize;
+    assert_eq!(run["tool"]["driver"]["rules"][rule_index]["id"], "E0001");
+}
+
+#[test]
+fn io_errors_keep_their_cause_and_path() {
+    let mut sources = SourceMap::new();
+    let err = sources.load("does/not/exist.lx").err().unwrap();
+
+    assert_eq!(err.err_type(), &ErrorType::IoError);
+    assert_eq!(err.path(), Some("does/not/exist.lx"));
+    assert_eq!(err.io_kind(), Some(io::ErrorKind::NotFound));
+    let cause = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+    assert_eq!(cause.kind(), io::ErrorKind::NotFound);
+    assert!(err.render(&sources).contains(" --> does/not/exist.lx\n"));
+
+    let outer = Error::new(ErrorType::MissingArgument).with_source(err);
+    let root = std::iter::successors(outer.source(), |e| (*e).source()).last();
+    assert!(root.unwrap().is::<io::Error>());
+}
@@ -0,0 +1,125 @@
+use lexer::modules::{
+    analyser::analyze_chars,
+    docs::{ItemKind, Param, collect_docs},
+    formatter::format_source,
+    source_map::SourceMap,
+    tokenizer::tokenize,
+};
+
+const SOURCE: &str = "//! Geometry helpers.
+
+/// A point.
+data Point {
+    x: f32,
+    y: f32
+}
+
+//// not a doc comment
+/// Adds two numbers.
+///
+/// Wraps on overflow.
+op add(a: i32, b: i32) i32 {
+    /// not attached to anything
+    success a + b;
+}
+
+op main() nret {
+    print(add(1, 2));
+}
+";
+
+fn lex_tokens(source: &str) -> Vec<String> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    let tokens = tokenize(&analyze_chars(source), file).unwrap();
+    tokens
+        .iter()
+        .map(|t| {
+            format!(
+                "{:?} {}:{}+{}",
+                tokens.interner().resolved(&t.token),
+                t.line,
+                t.column,
+                t.length
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn doc_comments_are_tokens_and_plain_comments_are_not() {
+    assert_eq!(
+        lex_tokens("//! module\n/// item\n// plain\n//// plain too\nop"),
+        [
+            "ModuleDocComment(\" module\") 1:1+10",
+            "DocComment(\" item\") 2:1+8",
+            "Op 5:1+2",
+        ]
+    );
+}
+
+#[test]
+fn doc_comments_attach_to_the_following_item() {
+    let mut sources = SourceMap::new();
+    let file = sources.add("geo.lx", SOURCE.to_string());
+    let docs = collect_docs(SOURCE, &tokenize(&analyze_chars(SOURCE), file).unwrap());
+
+    assert_eq!(docs.docs, "Geometry helpers.");
+    let summary: Vec<(&str, &str)> = docs
+        .items
+        .iter()
+        .map(|item| (item.name.as_str(), item.docs.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("Point", "A point."),
+            ("add", "Adds two numbers.\n\nWraps on overflow."),
+            ("main", ""),
+        ]
+    );
+    let param = |name: &str| Param {
+        name: name.to_string(),
+        ty: "i32".to_string(),
+    };
+    assert_eq!(
+        docs.items[1].kind,
+        ItemKind::Op {
+            params: vec![param("a"), param("b")],
+            returns: Some("i32".to_string()),
+        }
+    );
+
+    let markdown = docs.to_markdown("geo");
+    assert!(markdown.contains("```\nop add(a: i32, b: i32) i32\n```"));
+    assert!(markdown.contains("| `a` | `i32` |"));
+    assert!(markdown.contains("**Returns:** no return"));
+    assert!(!markdown.contains("not attached"));
+    assert!(docs.to_html("geo").contains("<p>Wraps on overflow.</p>"));
+
+    assert_eq!(format_source(&sources, file).unwrap(), SOURCE);
+}
+
+#[test]
+fn types_are_documented_as_written() {
+    let source = "op peek(p: *i32, names: [str]) str {\n    success names[0];\n}\n";
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    let docs = collect_docs(source, &tokenize(&analyze_chars(source), file).unwrap());
+
+    let param = |name: &str, ty: &str| Param {
+        name: name.to_string(),
+        ty: ty.to_string(),
+    };
+    assert_eq!(
+        docs.items[0].kind,
+        ItemKind::Op {
+            params: vec![param("p", "*i32"), param("names", "[str]")],
+            returns: Some("str".to_string()),
+        }
+    );
+    assert_eq!(
+        docs.items[0].signature(),
+        "op peek(p: *i32, names: [str]) str"
+    );
+}
@@ -0,0 +1,114 @@
+use lexer::modules::{
+    analyser::analyze_chars,
+    formatter::format_source,
+    source_map::SourceMap,
+    tokenizer::{TokenizeOptions, tokenize_with},
+};
+
+fn lex(source: &str, options: &TokenizeOptions) -> Vec<String> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    let tokens = tokenize_with(&analyze_chars(source), file, options).unwrap();
+    tokens
+        .iter()
+        .map(|t| {
+            format!(
+                "{:?} {}:{}+{}",
+                tokens.interner().resolved(&t.token),
+                t.line,
+                t.column,
+                t.length
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn else_if_spans_both_keywords() {
+    let options = TokenizeOptions::new();
+    for (source, length) in [
+        ("} else if", 7),
+        ("} else   if", 9),
+        ("} else\tif", 7),
+        ("} else\nif", 7),
+        ("} else\r\n  if", 9),
+    ] {
+        assert_eq!(
+            lex(source, &options),
+            ["RBrace 1:1+1", &format!("ElseIf 1:3+{length}")],
+            "{source:?}"
+        );
+    }
+}
+
+#[test]
+fn comments_between_else_and_if_are_kept_after_it() {
+    let options = TokenizeOptions::new().with_comments(true);
+    assert_eq!(
+        lex("else // otherwise\nif x", &options),
+        [
+            "ElseIf 1:1+20",
+            "Comment(\" otherwise\") 1:6+12",
+            "Identifier(\"x\") 2:4+1",
+        ]
+    );
+    assert_eq!(
+        lex("else // otherwise\nif x", &TokenizeOptions::new()),
+        ["ElseIf 1:1+20", "Identifier(\"x\") 2:4+1"]
+    );
+    // anything else in between keeps them apart
+    assert_eq!(
+        lex("else x if", &options),
+        ["Else 1:1+4", "Identifier(\"x\") 1:6+1", "If 1:8+2"]
+    );
+}
+
+#[test]
+fn block_comment_markers_keep_else_and_if_apart() {
+    // `.lx` has no block comments, `/*` is an operator like any other
+    assert_eq!(
+        lex(
+            "else /* c */ if",
+            &TokenizeOptions::new().with_comments(true)
+        ),
+        [
+            "Else 1:1+4",
+            "Symbol(\"/*\") 1:6+2",
+            "Identifier(\"c\") 1:9+1",
+            "Star 1:11+1",
+            "Slash 1:12+1",
+            "If 1:14+2",
+        ]
+    );
+}
+
+#[test]
+fn a_leading_if_is_just_an_if() {
+    assert_eq!(
+        lex("if x", &TokenizeOptions::new()),
+        ["If 1:1+2", "Identifier(\"x\") 1:4+1"]
+    );
+    assert_eq!(lex("if", &TokenizeOptions::new()), ["If 1:1+2"]);
+}
+
+#[test]
+fn else_and_if_can_stay_apart() {
+    assert_eq!(
+        lex(
+            "else\n  if",
+            &TokenizeOptions::new().with_split_else_if(true)
+        ),
+        ["Else 1:1+4", "If 2:3+2"]
+    );
+}
+
+#[test]
+fn formatting_joins_else_and_if_on_one_line() {
+    let source = "op f() nret {\n    if a {\n    } else\n    if b {\n    }\n}\n";
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    assert_eq!(
+        format_source(&sources, file).unwrap(),
+        "op f() nret {\n    if a {} else if b {}\n}\n"
+    );
+}
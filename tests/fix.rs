@@ -0,0 +1,61 @@
+use lexer::error_handling::{Applicability, Diagnostics, ErrorCode, Level};
+use lexer::modules::{fixer::fix_source, source_map::SourceMap};
+
+#[test]
+fn applies_machine_applicable_fixes_until_clean() {
+    let mut sources = SourceMap::new();
+    let diagnostics = Diagnostics::new().with_level(ErrorCode::TrailingWhitespace, Level::Warn);
+    let source = "let 1abc: i32 = 5;  \nlet s: string = \"Hello, world;\nlet t: i32 = 2;\n";
+
+    let fixed = fix_source(&mut sources, "inline.lx", source.to_string(), &diagnostics);
+
+    assert_eq!(
+        sources.get(fixed.file).text(),
+        "let _1abc: i32 = 5;\nlet s: string = \"Hello, world\";\nlet t: i32 = 2;\n"
+    );
+    assert_eq!(fixed.applied, 3);
+    assert!(fixed.remaining.is_empty());
+}
+
+#[test]
+fn suggests_keywords_for_typos_without_applying_them() {
+    let mut sources = SourceMap::new();
+    let diagnostics = Diagnostics::new();
+    let source = "whlie x < 5 {\n    retrun x;\n}\nlet chars: i32 = sucess;\n";
+
+    let fixed = fix_source(&mut sources, "inline.lx", source.to_string(), &diagnostics);
+
+    assert_eq!(fixed.applied, 0);
+    let suggested: Vec<&str> = fixed
+        .remaining
+        .iter()
+        .filter(|d| d.code() == Some(ErrorCode::MisspelledKeyword))
+        .flat_map(|d| d.suggestions())
+        .inspect(|s| assert_eq!(s.applicability, Applicability::MaybeIncorrect))
+        .map(|s| s.replacement.as_str())
+        .collect();
+    assert_eq!(suggested, ["while", "success", "success"]);
+}
+
+#[test]
+fn each_file_keeps_its_own_remaining_diagnostics() {
+    let mut sources = SourceMap::new();
+    let mut diagnostics = Diagnostics::new();
+    let mut texts = Vec::new();
+    for (name, source) in [
+        ("a.lx", "let x: i32 = sucess;\n"),
+        ("b.lx", "let 1abc: i32 = 5;\n"),
+    ] {
+        let fixed = fix_source(&mut sources, name, source.to_string(), &diagnostics);
+        texts.push(sources.get(fixed.file).text().to_string());
+        // reported the way `enoc fix` does, into the one shared sink
+        for diag in fixed.remaining {
+            diagnostics.push(diag);
+        }
+    }
+
+    assert_eq!(texts, ["let x: i32 = sucess;\n", "let _1abc: i32 = 5;\n"]);
+    let codes: Vec<Option<ErrorCode>> =
+        diagnostics.diagnostics().iter().map(|d| d.code()).collect();
+    assert_eq!(codes, [Some(ErrorCode::MisspelledKeyword)]);
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A fresh directory under the system temp dir with `files` written into it.
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("lexer-fmt-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    for (path, text) in files {
+        fs::write(root.join(path), text).unwrap();
+    }
+    root
+}
+
+fn fmt(root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_lexer"))
+        .arg("fmt")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .unwrap()
+}
+
+fn read(root: &Path, path: &str) -> String {
+    fs::read_to_string(root.join(path)).unwrap()
+}
+
+#[test]
+fn a_file_that_does_not_lex_does_not_stop_the_others() {
+    let root = tree(
+        "errors",
+        &[
+            ("a.lx", "let  a=1;\n"),
+            ("b.lx", "let s = \"open;\n"),
+            ("c.lx", "let  c=3;\n"),
+        ],
+    );
+    let output = fmt(&root, &["a.lx", "b.lx", "missing.lx", "c.lx"]);
+
+    assert!(!output.status.success());
+    assert_eq!(read(&root, "a.lx"), "let a = 1;\n");
+    assert_eq!(read(&root, "b.lx"), "let s = \"open;\n");
+    assert_eq!(read(&root, "c.lx"), "let c = 3;\n");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("error[L0001]"), "{stderr}");
+    assert!(stderr.contains("error[E0001]"), "{stderr}");
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn check_prints_a_diff_and_fails_without_writing() {
+    let root = tree(
+        "check",
+        &[
+            ("messy.lx", "let  a=1;\nlet b = 2;\n"),
+            ("tidy.lx", "let b = 2;\n"),
+        ],
+    );
+    let output = fmt(&root, &["--check", "messy.lx"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        concat!(
+            "--- messy.lx\n",
+            "+++ messy.lx (formatted)\n",
+            "@@ -1,2 +1,2 @@\n",
+            "-let  a=1;\n",
+            "+let a = 1;\n",
+            " let b = 2;\n",
+        )
+    );
+    assert_eq!(read(&root, "messy.lx"), "let  a=1;\nlet b = 2;\n");
+
+    let output = fmt(&root, &["--check", "tidy.lx"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(read(&root, "tidy.lx"), "let b = 2;\n");
+    fs::remove_dir_all(root).unwrap();
+}
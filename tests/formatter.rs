@@ -0,0 +1,90 @@
+use std::fs;
+
+use lexer::modules::formatter::{format_source, render_diff};
+use lexer::modules::source_map::SourceMap;
+
+fn format(source: &str) -> lexer::error_handling::Result<String> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    format_source(&sources, file)
+}
+
+#[test]
+fn formatting_is_idempotent_on_examples() {
+    let mut formatted_files = 0;
+    for entry in fs::read_dir("example_syntaxes").unwrap() {
+        let path = entry.unwrap().path();
+        let path = path.to_str().unwrap();
+        let source = fs::read_to_string(path).unwrap();
+
+        // the invalid_* / unclosed_* examples do not lex, so there is nothing to format
+        let Ok(once) = format(&source) else {
+            continue;
+        };
+        let twice = format(&once).unwrap();
+        assert_eq!(once, twice, "formatting {path} twice changed it");
+        formatted_files += 1;
+    }
+    assert!(formatted_files >= 4);
+}
+
+#[test]
+fn normalizes_layout() {
+    let source = "\n\nop  add(a:i32,b : i32)i32{success a+b;}\n\n\nop main() nret {\n\tlet x: i32 = -1;   \n  if x == 1 { print(x); }\n  else{ x=add(x,2) ; } // bump\n}";
+    let expected = "op add(a: i32, b: i32) i32 {\n    success a + b;\n}\n\nop main() nret {\n    let x: i32 = -1;\n    if x == 1 {\n        print(x);\n    } else {\n        x = add(x, 2);\n    } // bump\n}\n";
+    assert_eq!(format(source).unwrap(), expected);
+}
+
+#[test]
+fn keeps_comments() {
+    let source = "// header\n\nloop {\n// inside\n}\n";
+    let expected = "// header\n\nloop {\n    // inside\n}\n";
+    assert_eq!(format(source).unwrap(), expected);
+}
+
+#[test]
+fn a_byte_order_mark_survives_formatting() {
+    // only the first U+FEFF is a byte order mark, a second one is text that is kept
+    for (bom, text) in [("\u{feff}", ""), ("\u{feff}\u{feff}", "\u{feff}")] {
+        let mut sources = SourceMap::new();
+        let file = sources.add("inline.lx", format!("{bom}if x {{ a; }} else {{ b; }}\n"));
+        let formatted = format_source(&sources, file).unwrap();
+        assert_eq!(
+            formatted,
+            format!("{text}if x {{\n    a;\n}} else {{\n    b;\n}}\n"),
+            "{bom:?}"
+        );
+        assert!(sources.get(file).has_bom());
+
+        // written back with its mark, the way `fmt` does, the file is formatted
+        let written = sources.add("inline.lx", format!("\u{feff}{formatted}"));
+        assert!(sources.get(written).has_bom());
+        assert_eq!(sources.get(written).text(), formatted);
+        assert_eq!(format_source(&sources, written).unwrap(), formatted);
+    }
+}
+
+#[test]
+fn diffs_show_changed_lines_in_hunks_with_two_lines_of_context() {
+    let original =
+        "let  a=1;\nlet b = 2;\nlet c = 3;\nlet d = 4;\nlet e = 5;\nlet f = 6;\nlet  g=7;\n";
+    let formatted = format(original).unwrap();
+    assert_eq!(
+        render_diff("f.lx", original, &formatted),
+        concat!(
+            "--- f.lx\n",
+            "+++ f.lx (formatted)\n",
+            "@@ -1,3 +1,3 @@\n",
+            "-let  a=1;\n",
+            "+let a = 1;\n",
+            " let b = 2;\n",
+            " let c = 3;\n",
+            // five unchanged lines in between are too many for one hunk
+            "@@ -5,3 +5,3 @@\n",
+            " let e = 5;\n",
+            " let f = 6;\n",
+            "-let  g=7;\n",
+            "+let g = 7;\n",
+        )
+    );
+}
@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+use lexer::modules::{
+    grammar::GrammarFormat,
+    language::{LX, LanguageSpec},
+};
+
+fn words(text: &str) -> HashSet<&str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+#[test]
+fn every_keyword_and_operator_is_highlighted() {
+    for format in GrammarFormat::ALL {
+        let grammar = format.generate(&LX, "lx");
+        let found = words(&grammar);
+        for keyword in LX.keywords() {
+            assert!(found.contains(keyword), "{keyword} missing in {format:?}");
+        }
+        for operator in LX.operators() {
+            let escaped: String = match format {
+                GrammarFormat::Vim => operator.to_string(),
+                _ => operator.chars().map(|c| format!("\\{c}")).collect(),
+            };
+            let escaped = match format {
+                GrammarFormat::TextMate => escaped.replace('\\', "\\\\"),
+                _ => escaped,
+            };
+            assert!(
+                grammar.contains(&escaped),
+                "{operator} missing in {format:?}"
+            );
+        }
+    }
+    let textmate: serde_json::Value =
+        serde_json::from_str(&GrammarFormat::TextMate.generate(&LX, "lx")).unwrap();
+    assert_eq!(textmate["scopeName"], "source.lx");
+}
+
+#[test]
+fn dialect_grammars_follow_the_spec() {
+    let spec = LanguageSpec::from_toml(
+        "[keywords]\nfn = \"Op\"\nwhen = \"If\"\n\n[comments]\nline = \"#\"\n",
+    )
+    .unwrap();
+    let vim = GrammarFormat::Vim.generate(&spec, "dl");
+    assert!(vim.contains("syn keyword dlControl when\n"));
+    assert!(vim.contains("syn keyword dlDeclaration fn\n"));
+    assert!(vim.contains("syn match dlComment \"\\V#\\.\\*\\$\"\n"));
+    assert!(!words(&vim).contains("nret"));
+    assert!(!vim.contains("DocComment"));
+}
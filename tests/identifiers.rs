@@ -0,0 +1,92 @@
+use lexer::error_handling::{Diagnostics, ErrorCode, Span};
+use lexer::modules::{
+    analyser::analyze_chars, language::LX, lints::lint, source_map::SourceMap, tokenizer::tokenize,
+    tokens::Token,
+};
+
+fn identifiers(source: &str) -> Vec<(String, usize)> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    let tokens = tokenize(&analyze_chars(source), file).unwrap();
+    tokens
+        .iter()
+        .filter_map(|t| match &t.token {
+            Token::Identifier(name) => {
+                Some((tokens.interner().resolve(*name).to_string(), t.length))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn identifiers_follow_xid_and_are_nfc_normalized() {
+    let names = identifiers("let değişken: i32 = café + cafe\u{301} + _x1 + переменная;\n");
+    assert_eq!(
+        names,
+        [
+            ("değişken".to_string(), 8),
+            ("café".to_string(), 4),
+            // same name as the precomposed spelling, but it still covers five source chars
+            ("café".to_string(), 5),
+            ("_x1".to_string(), 3),
+            ("переменная".to_string(), 10),
+        ]
+    );
+
+    let mut sources = SourceMap::new();
+    for source in [
+        "let a\u{200b}b: i32 = 1;",
+        "let a\u{b7}b: i32 = 1;",
+        "let ½: i32 = 1;",
+    ] {
+        let file = sources.add("inline.lx", source.to_string());
+        let err = tokenize(&analyze_chars(source), file).err();
+        let code = err.as_ref().and_then(|e| e.code());
+        // U+00B7 MIDDLE DOT is XID_Continue; the others are not identifier characters
+        if source.contains('\u{b7}') {
+            assert!(err.is_none(), "{source:?}");
+        } else {
+            assert!(
+                matches!(
+                    code,
+                    Some(ErrorCode::UnexpectedCharacter | ErrorCode::IdentifierStartsWithDigit)
+                ),
+                "{source:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn mixed_script_identifiers_are_reported_with_their_span() {
+    let mut sources = SourceMap::new();
+    let source = "let dаta: i32 = 1;\nlet değişken: i32 = 2;\nlet рор: i32 = 3;\n";
+    let file = sources.add("inline.lx", source.to_string());
+    let tokens = tokenize(&analyze_chars(source), file).unwrap();
+    let mut diagnostics = Diagnostics::new();
+    lint(file, sources.get(file), &tokens, &LX, &mut diagnostics);
+
+    let warnings: Vec<_> = diagnostics
+        .diagnostics()
+        .iter()
+        .filter(|d| d.code() == Some(ErrorCode::ConfusableIdentifier))
+        .collect();
+    assert_eq!(warnings.len(), 2);
+
+    let mixed = warnings[0];
+    assert_eq!(
+        mixed.message(),
+        "Identifier 'dаta' mixes Latin and Cyrillic characters!"
+    );
+    assert_eq!(
+        (mixed.line(), mixed.column(), mixed.length()),
+        (Some(1), Some(5), Some(4))
+    );
+    let cyrillic = &mixed.labels()[1];
+    assert_eq!(cyrillic.span, Span::new(1, 6, 1));
+    assert_eq!(mixed.suggestions()[0].replacement, "data");
+
+    assert_eq!(warnings[1].message(), "Identifier 'рор' looks like 'pop'!");
+    assert_eq!(warnings[1].line(), Some(3));
+}
@@ -0,0 +1,200 @@
+use proptest::prelude::*;
+
+use lexer::error_handling::{Error, Result};
+use lexer::modules::{
+    analyser::analyze_chars,
+    incremental::{Relexed, TextEdit, relex},
+    source_map::{FileId, SourceMap},
+    token_stream::TokenStream,
+    tokenizer::{TokenizeOptions, tokenize_with},
+};
+
+fn file() -> FileId {
+    SourceMap::new().add("inline.lx", String::new())
+}
+
+fn lex(source: &str, options: &TokenizeOptions) -> Result<TokenStream> {
+    tokenize_with(&analyze_chars(source), file(), options)
+}
+
+/// Every token with its position, length, flag and bytes, then the end of the file.
+fn describe(tokens: &TokenStream) -> Vec<String> {
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            format!(
+                "{:?} {}:{}+{} {:?} {:?}",
+                tokens.interner().resolved(&t.token),
+                t.line,
+                t.column,
+                t.length,
+                t.soft_keyword
+                    .as_ref()
+                    .map(|k| tokens.interner().resolved(k)),
+                tokens.byte_range(i)
+            )
+        })
+        .chain([format!("Eof {}:{}", tokens.eof().line, tokens.eof().column)])
+        .collect()
+}
+
+fn outcome(result: std::result::Result<&TokenStream, &Error>) -> Vec<String> {
+    match result {
+        Ok(tokens) => describe(tokens),
+        Err(err) => vec![format!(
+            "{:?} {:?} {:?}",
+            err.code(),
+            err.line(),
+            err.column()
+        )],
+    }
+}
+
+fn edited(source: &str, edit: &TextEdit, options: &TokenizeOptions) -> Result<Relexed> {
+    let previous = lex(source, options).unwrap();
+    relex(&previous, source, edit, file(), options)
+}
+
+#[test]
+fn an_edit_inside_a_line_only_relexes_that_line() {
+    let source = "let a = 1;\nlet b = 2;\nlet c = 3;\n";
+    let at = source.find('b').unwrap();
+    let edit = TextEdit::new(at..at + 1, "bee + b");
+    let relexed = edited(source, &edit, &TokenizeOptions::new()).unwrap();
+
+    let expected = lex(&edit.apply(source), &TokenizeOptions::new());
+    assert_eq!(describe(&relexed.tokens), outcome(expected.as_ref()));
+    // `bee` and `+` are new, `b` reads the same as before and only moved
+    assert_eq!(relexed.changed, 6..8);
+}
+
+#[test]
+fn opening_a_string_relexes_as_far_as_it_reaches() {
+    let source = "let s = 1;\nlet t = 2;\n";
+    let options = TokenizeOptions::new();
+    for (range, text) in [(8..9, "\"\"\"\n"), (19..19, "\"\"\""), (4..5, "\"x {")] {
+        let edit = TextEdit::new(range, text);
+        let expected = lex(&edit.apply(source), &options);
+        let relexed = edited(source, &edit, &options).map(|r| r.tokens);
+        assert_eq!(
+            outcome(relexed.as_ref()),
+            outcome(expected.as_ref()),
+            "{edit:?}"
+        );
+    }
+}
+
+#[test]
+fn comments_else_if_and_interpolations_resynchronize_correctly() {
+    let source = "if a {} else x {}\nlet s = \"v {b} w\"; // note\nop f() nret {}\n";
+    let options = TokenizeOptions::new().with_comments(true);
+    let x = source.find('x').unwrap();
+    let b = source.find('b').unwrap();
+    for edit in [
+        TextEdit::new(x..x + 1, "if"),
+        TextEdit::new(b..b + 1, "b + 1"),
+        TextEdit::new(b..b + 1, "}"),
+        TextEdit::new(0..0, "// "),
+        TextEdit::new(source.len()..source.len(), "let"),
+    ] {
+        let expected = lex(&edit.apply(source), &options);
+        let relexed = edited(source, &edit, &options).map(|r| r.tokens);
+        assert_eq!(
+            outcome(relexed.as_ref()),
+            outcome(expected.as_ref()),
+            "{edit:?}"
+        );
+    }
+}
+
+const PIECES: [&str; 22] = [
+    "let",
+    "x",
+    "data",
+    "if",
+    "else",
+    "{",
+    "}",
+    "(",
+    ")",
+    ";",
+    "-",
+    "<",
+    "1",
+    "é",
+    " ",
+    " ",
+    "\n",
+    "\"s\"",
+    "\"a {x} b\"",
+    "// c",
+    "r#\"q\"#",
+    "\"\"\"\nab\n\"\"\"",
+];
+
+fn source() -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(PIECES.to_vec()), 0..30).prop_map(|p| p.concat())
+}
+
+fn replacement() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop::sample::select(
+            PIECES
+                .iter()
+                .copied()
+                .chain(["\"", "//", "{", "\"\"\"", "r#", "f", "9"])
+                .collect::<Vec<_>>(),
+        ),
+        0..4,
+    )
+    .prop_map(|p| p.concat())
+}
+
+proptest! {
+    #[test]
+    fn relexing_an_edit_matches_lexing_everything(
+        source in source(),
+        from in 0.0..=1.0f64,
+        len in 0.0..=1.0f64,
+        text in replacement(),
+        keep_comments in any::<bool>(),
+    ) {
+        let options = TokenizeOptions::new().with_comments(keep_comments);
+        let Ok(previous) = lex(&source, &options) else {
+            return Ok(());
+        };
+        // snap the edit to char boundaries
+        let bounds: Vec<usize> = source
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([source.len()])
+            .collect();
+        let start = ((bounds.len() - 1) as f64 * from) as usize;
+        let end = start + ((bounds.len() - 1 - start) as f64 * len * 0.3) as usize;
+        let edit = TextEdit::new(bounds[start]..bounds[end], &text);
+
+        let expected = lex(&edit.apply(&source), &options);
+        let relexed = relex(&previous, &source, &edit, file(), &options);
+        let relexed = relexed.map(|r| (r.changed.clone(), r.tokens));
+        prop_assert_eq!(
+            outcome(relexed.as_ref().map(|(_, tokens)| tokens)),
+            outcome(expected.as_ref())
+        );
+
+        // outside `changed`, tokens are the old ones, the ones after it moved
+        if let Ok((changed, tokens)) = &relexed {
+            let old = describe(&previous);
+            let new = describe(tokens);
+            prop_assert_eq!(&new[..changed.start], &old[..changed.start]);
+            let after = tokens.len() - changed.end;
+            prop_assert!(after <= previous.len());
+            for i in 0..after {
+                prop_assert_eq!(
+                    &tokens[changed.end + i].token,
+                    &previous[previous.len() - after + i].token
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use lexer::modules::{
+    analyser::analyze_chars,
+    interner::{Interner, Symbol},
+    language::LX,
+    source_map::SourceMap,
+    token_stream::TokenStream,
+    tokenizer::tokenize,
+    tokens::Token,
+};
+
+fn lex(source: &str) -> TokenStream {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    tokenize(&analyze_chars(source), file).unwrap()
+}
+
+fn identifiers(tokens: &TokenStream) -> Vec<Symbol> {
+    tokens
+        .iter()
+        .filter_map(|t| match t.token {
+            Token::Identifier(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn the_interner_hands_out_one_symbol_per_text() {
+    let mut interner = Interner::new();
+    assert!(interner.is_empty());
+    let x = interner.intern("x");
+    let y = interner.intern("y");
+    assert_ne!(x, y);
+    assert_eq!(interner.intern("x"), x);
+    assert_eq!(interner.len(), 2);
+    assert_eq!(interner.resolve(y), "y");
+    assert_eq!(interner.get("y"), Some(y));
+    assert_eq!(interner.get("z"), None);
+}
+
+#[test]
+fn identifiers_with_the_same_name_share_a_symbol() {
+    // precomposed and combining `é` are the same name
+    let tokens = lex("let café = x + x + cafe\u{301};\n");
+    let names = identifiers(&tokens);
+    assert_eq!(names.len(), 4);
+    assert_eq!(names[1], names[2]);
+    assert_eq!(names[0], names[3]);
+    assert_eq!(tokens.interner().resolve(names[0]), "café");
+    // the language's symbols come first, then one per distinct name
+    assert_eq!(tokens.interner().len(), LX.interner().len() + 2);
+}
+
+#[test]
+fn operator_symbols_are_the_same_in_every_stream() {
+    let first = lex("a and b, c");
+    let second = lex("let d = e, f or g;");
+    let symbols = |tokens: &TokenStream| -> Vec<Symbol> {
+        tokens
+            .iter()
+            .filter_map(|t| match t.token {
+                Token::Symbol(symbol) => Some(symbol),
+                _ => None,
+            })
+            .collect()
+    };
+    let comma = LX.interner().get(",").unwrap();
+    assert_eq!(symbols(&first)[1], comma);
+    assert_eq!(symbols(&second)[0], comma);
+    assert_eq!(
+        LX.keyword("and"),
+        Some(Token::Symbol(LX.interner().get("&&").unwrap()))
+    );
+}
+
+#[test]
+fn resolved_tokens_print_their_text() {
+    let tokens = lex("let x = a and b;");
+    let shown: Vec<String> = tokens
+        .iter()
+        .map(|t| format!("{:?}", tokens.interner().resolved(&t.token)))
+        .collect();
+    assert_eq!(
+        shown,
+        [
+            "Let",
+            "Identifier(\"x\")",
+            "Equals",
+            "Identifier(\"a\")",
+            "Symbol(\"&&\")",
+            "Identifier(\"b\")",
+            "Semicolon",
+        ]
+    );
+    assert!(format!("{:?}", tokens[1].token).starts_with("Identifier(Symbol("));
+}
@@ -0,0 +1,118 @@
+use lexer::error_handling::{Error, ErrorCode, Result};
+use lexer::modules::{
+    analyser::analyze_chars, formatter::format_source, source_map::SourceMap,
+    token_stream::TokenStream, tokenizer::tokenize,
+};
+
+fn lex(source: &str) -> Result<TokenStream> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    tokenize(&analyze_chars(source), file)
+}
+
+/// Every token as `Token line:column+length`.
+fn tokens(source: &str) -> Vec<String> {
+    let tokens = lex(source).unwrap();
+    tokens
+        .iter()
+        .map(|t| {
+            format!(
+                "{:?} {}:{}+{}",
+                tokens.interner().resolved(&t.token),
+                t.line,
+                t.column,
+                t.length
+            )
+        })
+        .collect()
+}
+
+fn lex_error(source: &str) -> Error {
+    match lex(source) {
+        Ok(_) => panic!("expected an error for {source:?}"),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn interpolated_strings_split_into_fragments_and_expressions() {
+    assert_eq!(
+        tokens("\"Hello, {name}! You have {count + 1} items\""),
+        [
+            "StringStart 1:1+1",
+            "StringFragment(\"Hello, \") 1:2+7",
+            "InterpolationStart 1:9+1",
+            "Identifier(\"name\") 1:10+4",
+            "InterpolationEnd 1:14+1",
+            "StringFragment(\"! You have \") 1:15+11",
+            "InterpolationStart 1:26+1",
+            "Identifier(\"count\") 1:27+5",
+            "Plus 1:33+1",
+            "Number(1) 1:35+1",
+            "InterpolationEnd 1:36+1",
+            "StringFragment(\" items\") 1:37+6",
+            "StringEnd 1:43+1",
+        ]
+    );
+
+    // text after an expression holding a multi-line string starts on the line of its `}`
+    let after = tokens("\"a {\"\"\"\nb\n\"\"\"} c\"");
+    assert_eq!(
+        after[4..],
+        [
+            "InterpolationEnd 3:4+1",
+            "StringFragment(\" c\") 3:5+2",
+            "StringEnd 3:7+1"
+        ]
+    );
+}
+
+#[test]
+fn doubled_braces_and_nested_expressions() {
+    assert_eq!(tokens("\"{{a}}\""), ["StringLiteral(\"{a}\") 1:1+7"]);
+
+    let nested: Vec<String> = tokens("\"x{f(\"y{z}\")}\"")
+        .into_iter()
+        .map(|t| t.split(' ').next().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        nested,
+        [
+            "StringStart",
+            "StringFragment(\"x\")",
+            "InterpolationStart",
+            "Identifier(\"f\")",
+            "LeftParen",
+            "StringStart",
+            "StringFragment(\"y\")",
+            "InterpolationStart",
+            "Identifier(\"z\")",
+            "InterpolationEnd",
+            "StringEnd",
+            "RightParen",
+            "InterpolationEnd",
+            "StringEnd",
+        ]
+    );
+}
+
+#[test]
+fn errors_point_into_the_embedded_expression() {
+    let err = lex_error("let s: string = \"n = {1abc}\";\n");
+    assert_eq!(err.code(), Some(ErrorCode::IdentifierStartsWithDigit));
+    assert_eq!((err.line(), err.column()), (Some(1), Some(23)));
+
+    let err = lex_error("let s: string = \"n = {x\nlet t: i32 = 1;\n");
+    assert_eq!(err.code(), Some(ErrorCode::UnclosedInterpolation));
+    assert_eq!((err.line(), err.column()), (Some(1), Some(22)));
+}
+
+#[test]
+fn interpolated_strings_format_verbatim_and_last_words_are_kept() {
+    assert_eq!(tokens("nret x"), ["Nret 1:1+4", "Identifier(\"x\") 1:6+1"]);
+
+    let source = "let s: string = \"a {b+1} c\";\n";
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    assert_eq!(format_source(&sources, file).unwrap(), source);
+}
@@ -0,0 +1,172 @@
+use lexer::error_handling::{ErrorCode, Result};
+use lexer::modules::{
+    analyser::analyze_chars,
+    docs::{ItemKind, collect_docs},
+    language::LanguageSpec,
+    source_map::SourceMap,
+    token_stream::TokenStream,
+    tokenizer::{TokenizeOptions, tokenize_with},
+    tokens::{FloatType, IntType, IntWidth, Token},
+};
+
+fn lex(source: &str, language: LanguageSpec) -> Result<TokenStream> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    let options = TokenizeOptions::new().with_language(language);
+    tokenize_with(&analyze_chars(source), file, &options)
+}
+
+/// Every token as `Token line:column+length`, followed by the keyword it could be.
+fn outcome(result: Result<TokenStream>) -> Vec<String> {
+    match result {
+        Ok(tokens) => tokens
+            .iter()
+            .map(|t| {
+                let token = tokens.interner().resolved(&t.token);
+                let mut text = format!("{:?} {}:{}+{}", token, t.line, t.column, t.length);
+                if let Some(keyword) = &t.soft_keyword {
+                    let keyword = tokens.interner().resolved(keyword);
+                    text.push_str(&format!(" could be {keyword:?}"));
+                }
+                text
+            })
+            .collect(),
+        Err(err) => vec![format!(
+            "{:?} {:?} {:?}",
+            err.code(),
+            err.line(),
+            err.column()
+        )],
+    }
+}
+
+#[test]
+fn contextual_keywords_are_flagged_identifiers() {
+    assert_eq!(
+        outcome(lex(
+            "data Point { data: i32 }\nlet data = 1;",
+            LanguageSpec::default()
+        )),
+        [
+            "Identifier(\"data\") 1:1+4 could be Data",
+            "Identifier(\"Point\") 1:6+5",
+            "LBrace 1:12+1",
+            "Identifier(\"data\") 1:14+4 could be Data",
+            "Colon 1:18+1",
+            "IntType(IntType { signed: true, width: W32 }) 1:20+3",
+            "RBrace 1:24+1",
+            "Let 2:1+3",
+            "Identifier(\"data\") 2:5+4 could be Data",
+            "Equals 2:10+1",
+            "Number(1) 2:12+1",
+            "Semicolon 2:13+1",
+        ]
+    );
+}
+
+#[test]
+fn raw_identifiers_escape_any_keyword() {
+    assert_eq!(
+        outcome(lex(
+            "let r#loop = r#data;\nr\"raw\" r#\"raw\"#",
+            LanguageSpec::default()
+        )),
+        [
+            "Let 1:1+3",
+            "Identifier(\"loop\") 1:5+6",
+            "Equals 1:12+1",
+            "Identifier(\"data\") 1:14+6",
+            "Semicolon 1:20+1",
+            "StringLiteral(\"raw\") 2:1+6",
+            "StringLiteral(\"raw\") 2:8+8",
+        ]
+    );
+    for source in ["r#", "r#1x", "r##loop"] {
+        assert_eq!(
+            outcome(lex(source, LanguageSpec::default())),
+            ["Some(UnexpectedCharacter) Some(1) Some(1)"],
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn contextual_keywords_declare_items_only_before_a_name() {
+    let source = "data Point { data: i32 }\nlet data: i32 = 1;\n";
+    let docs = collect_docs(source, &lex(source, LanguageSpec::default()).unwrap());
+    let items: Vec<(&str, bool)> = docs
+        .items
+        .iter()
+        .map(|item| {
+            (
+                item.name.as_str(),
+                matches!(item.kind, ItemKind::Data { .. }),
+            )
+        })
+        .collect();
+    assert_eq!(items, [("Point", true), ("data", false)]);
+}
+
+#[test]
+fn dialects_choose_their_contextual_keywords() {
+    let dialect = LanguageSpec::from_toml("[contextual_keywords]\nloop = \"Loop\"").unwrap();
+    assert_eq!(
+        outcome(lex("loop data", dialect)),
+        [
+            "Identifier(\"loop\") 1:1+4 could be Loop",
+            "Identifier(\"data\") 1:6+4",
+        ]
+    );
+    // own keywords replace the contextual ones of the built-in language too
+    let reserved = LanguageSpec::from_toml("[keywords]\ndata = \"Data\"").unwrap();
+    assert_eq!(outcome(lex("data", reserved)), ["Data 1:1+4"]);
+
+    let err = LanguageSpec::from_toml(
+        "[keywords]\nloop = \"Loop\"\n[contextual_keywords]\nloop = \"Loop\"",
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.code(), Some(ErrorCode::InvalidLanguageSpec));
+}
+
+#[test]
+fn primitive_types_carry_their_width_and_signedness() {
+    assert_eq!(
+        outcome(lex("u8 i128 usize f64 str", LanguageSpec::default())),
+        [
+            "IntType(IntType { signed: false, width: W8 }) 1:1+2",
+            "IntType(IntType { signed: true, width: W128 }) 1:4+4",
+            "IntType(IntType { signed: false, width: Size }) 1:9+5",
+            "FloatType(F64) 1:15+3",
+            "String 1:19+3",
+        ]
+    );
+    for ty in IntType::all() {
+        let tokens = lex(ty.name(), LanguageSpec::default()).unwrap();
+        let token = &tokens.iter().next().unwrap().token;
+        assert!(
+            matches!(token, Token::IntType(lexed) if *lexed == ty),
+            "{}",
+            ty.name()
+        );
+    }
+    assert_eq!(IntType::all().count(), 12);
+    assert_eq!(
+        IntType::from_name("isize").map(|ty| ty.width),
+        Some(IntWidth::Size)
+    );
+    assert_eq!(FloatType::from_name("f32"), Some(FloatType::F32));
+
+    let dialect = LanguageSpec::from_toml("[keywords]\nlong = \"IntType(i64)\"").unwrap();
+    assert_eq!(
+        outcome(lex("long", dialect)),
+        ["IntType(IntType { signed: true, width: W64 }) 1:1+4"]
+    );
+    for spec in [
+        "[keywords]\nint = \"IntType\"",
+        "[keywords]\nreal = \"FloatType(f16)\"",
+    ] {
+        let err = LanguageSpec::from_toml(spec).err().unwrap();
+        assert_eq!(err.code(), Some(ErrorCode::InvalidLanguageSpec), "{spec}");
+    }
+}
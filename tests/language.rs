@@ -0,0 +1,171 @@
+use std::fs;
+
+use lexer::error_handling::{ErrorCode, Result};
+use lexer::modules::{
+    analyser::analyze_chars,
+    language::LanguageSpec,
+    source_map::SourceMap,
+    token_stream::TokenStream,
+    tokenizer::{TokenizeOptions, tokenize_with},
+};
+
+const DIALECT: &str = r###"
+[keywords]
+fn = "Op"
+var = "Let"
+int = "IntType(i32)"
+return = "Success"
+
+[operators]
+"=" = "Equals"
+":" = "Colon"
+";" = "Semicolon"
+"(" = "LeftParen"
+")" = "RightParen"
+"{" = "LBrace"
+"}" = "RBrace"
+"+" = "Plus"
+"," = "Symbol"
+"=>" = "Arrow"
+
+[comments]
+line = "#"
+doc = "##"
+
+[strings]
+quote = "'"
+char_quote = "`"
+
+[identifiers]
+unicode = false
+start = "_$"
+"###;
+
+fn lex(source: &str, language: LanguageSpec) -> Result<TokenStream> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline", source.to_string());
+    let options = TokenizeOptions::new().with_language(language);
+    tokenize_with(&analyze_chars(source), file, &options)
+}
+
+/// Every token as `Token line:column+length`, or the code and position of the error.
+fn outcome(result: Result<TokenStream>) -> Vec<String> {
+    match result {
+        Ok(tokens) => tokens
+            .iter()
+            .map(|t| {
+                format!(
+                    "{:?} {}:{}+{}",
+                    tokens.interner().resolved(&t.token),
+                    t.line,
+                    t.column,
+                    t.length
+                )
+            })
+            .collect(),
+        Err(err) => vec![format!(
+            "{:?} {:?} {:?}",
+            err.code(),
+            err.line(),
+            err.column()
+        )],
+    }
+}
+
+#[test]
+fn written_out_lx_spec_lexes_like_the_built_in_one() {
+    let lx = LanguageSpec::load("example_syntaxes/lx.toml").unwrap();
+    for entry in fs::read_dir("example_syntaxes").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "lx") {
+            continue;
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            outcome(lex(&text, lx.clone())),
+            outcome(lex(&text, LanguageSpec::default())),
+            "{}",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn dialects_bring_their_own_keywords_operators_comments_and_quotes() {
+    let dialect = LanguageSpec::from_toml(DIALECT).unwrap();
+    assert_eq!(
+        outcome(lex(
+            "## adds\nfn add($a: int) { # sum\n    return $a => 'x';\n}\n",
+            dialect.clone()
+        )),
+        [
+            "DocComment(\" adds\") 1:1+7",
+            "Op 2:1+2",
+            "Identifier(\"add\") 2:4+3",
+            "LeftParen 2:7+1",
+            "Identifier(\"$a\") 2:8+2",
+            "Colon 2:10+1",
+            "IntType(IntType { signed: true, width: W32 }) 2:12+3",
+            "RightParen 2:15+1",
+            "LBrace 2:17+1",
+            "Success 3:5+6",
+            "Identifier(\"$a\") 3:12+2",
+            "Arrow 3:15+2",
+            "StringLiteral(\"x\") 3:18+3",
+            "Semicolon 3:21+1",
+            "RBrace 4:1+1",
+        ]
+    );
+    // `let` is not a keyword of the dialect, and `é` is not an ASCII identifier
+    assert_eq!(
+        outcome(lex("let", dialect.clone())),
+        ["Identifier(\"let\") 1:1+3"]
+    );
+    assert_eq!(
+        outcome(lex("café", dialect)),
+        ["Some(UnexpectedCharacter) Some(1) Some(1)"]
+    );
+}
+
+#[test]
+fn invalid_specs_are_reported() {
+    for spec in [
+        "keywords = 1",
+        "[keywords]\nfn = \"Fn\"",
+        "[strings]\nquote = \"''\"",
+        "[colors]",
+        "not toml",
+    ] {
+        let err = LanguageSpec::from_toml(spec).err().unwrap();
+        assert_eq!(err.code(), Some(ErrorCode::InvalidLanguageSpec), "{spec}");
+    }
+    let err = LanguageSpec::load("example_syntaxes/missing.toml")
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), Some(ErrorCode::Io));
+}
+
+#[test]
+fn quotes_may_not_hide_each_other_or_comments_and_operators() {
+    for (spec, message) in [
+        (
+            "[strings]\nquote = \"'\"",
+            "'strings.quote' and 'strings.char_quote' are both `'`",
+        ),
+        (
+            "[strings]\nchar_quote = \"#\"\n[comments]\nline = \"#\"",
+            "'strings.char_quote' and 'comments.line' both start with `#`",
+        ),
+        (
+            "[strings]\nquote = \"|\"\n[operators]\n\"||\" = \"Symbol\"",
+            "'strings.quote' and 'operators.||' both start with `|`",
+        ),
+    ] {
+        let err = LanguageSpec::from_toml(spec).err().unwrap();
+        assert_eq!(err.code(), Some(ErrorCode::InvalidLanguageSpec), "{spec}");
+        assert_eq!(
+            err.message(),
+            format!("Invalid language definition! -> {message}")
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use std::fs;
+
+use lexer::error_handling::Result;
+use lexer::modules::{
+    analyser::{AnalyzeOptions, AnalyzedChar, CharKind, analyze_chars, analyze_chars_with},
+    source_map::SourceMap,
+    token_stream::TokenStream,
+    tokenizer::tokenize,
+    tokens::Token,
+};
+
+fn examples() -> Vec<(String, String)> {
+    let mut files: Vec<_> = fs::read_dir("example_syntaxes")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "lx"))
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .map(|path| {
+            let text = fs::read_to_string(&path).unwrap();
+            (path.display().to_string(), text)
+        })
+        .collect()
+}
+
+fn lex(text: &str) -> Result<TokenStream> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", text.to_string());
+    tokenize(&analyze_chars(text), file)
+}
+
+/// Tokens and positions, or the code and position of the error.
+fn outcome(result: Result<TokenStream>) -> Vec<String> {
+    match result {
+        Ok(tokens) => tokens.iter().map(|t| format!("{t:?}")).collect(),
+        Err(err) => vec![format!(
+            "{:?} {:?} {:?}",
+            err.code(),
+            err.line(),
+            err.column()
+        )],
+    }
+}
+
+#[test]
+fn crlf_and_bom_lex_like_lf() {
+    for (name, text) in examples() {
+        let lf = outcome(lex(&text));
+        let crlf = text.replace('\n', "\r\n");
+        assert_eq!(outcome(lex(&crlf)), lf, "CRLF copy of {name}");
+        assert_eq!(
+            outcome(lex(&format!("\u{feff}{crlf}"))),
+            lf,
+            "CRLF copy of {name} with a BOM"
+        );
+        let cr = text.replace('\n', "\r");
+        assert_eq!(outcome(lex(&cr)), lf, "CR copy of {name}");
+    }
+}
+
+#[test]
+fn multi_line_strings_hold_plain_newlines() {
+    let tokens =
+        lex("let s: string = \"\"\"\r\n  a\r\n  b\r  c\r\n  \"\"\";\r\nlet t: i32 = 1;\r\n")
+            .unwrap();
+    let Token::StringLiteral(s) = &tokens.iter().nth(5).unwrap().token else {
+        panic!("expected a string literal");
+    };
+    assert_eq!(s, "a\nb\nc");
+    let last = tokens.iter().next_back().unwrap();
+    assert_eq!((last.line, last.column), (6, 15));
+
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", "\u{feff}a\r\nb\rc\n".to_string());
+    let source = sources.get(file);
+    assert_eq!(source.line_count(), 4);
+    assert_eq!(
+        (source.line(1), source.line(2), source.line(3)),
+        (Some("a"), Some("b"), Some("c"))
+    );
+}
+
+#[test]
+fn tabs_advance_visual_columns_to_the_next_stop() {
+    for (name, text) in examples() {
+        let tabbed: String = text
+            .lines()
+            .map(|line| {
+                let spaces = line.len() - line.trim_start_matches(' ').len();
+                format!("{}{}\n", "\t".repeat(spaces / 4), &line[spaces / 4 * 4..])
+            })
+            .collect();
+
+        let original = analyze_chars(&text);
+        let tabbed = analyze_chars_with(&tabbed, &AnalyzeOptions::new().with_tab_width(4));
+        let visible = |chars: &[AnalyzedChar]| -> Vec<(usize, usize)> {
+            chars
+                .iter()
+                .filter(|c| matches!(c.kind, CharKind::Char(_)))
+                .map(|c| (c.line, c.visual_column))
+                .collect()
+        };
+        assert_eq!(
+            visible(&tabbed),
+            visible(&original),
+            "tabbed copy of {name}"
+        );
+    }
+
+    let chars = analyze_chars_with("\ta\t\tb", &AnalyzeOptions::new().with_tab_width(8));
+    let columns: Vec<(usize, usize)> = chars.iter().map(|c| (c.column, c.visual_column)).collect();
+    assert_eq!(columns, [(1, 1), (2, 9), (3, 10), (4, 17), (5, 25)]);
+}
@@ -0,0 +1,82 @@
+use lexer::error_handling::{Diagnostics, ErrorCode, Level, Severity};
+use lexer::modules::{
+    analyser::analyze_chars,
+    language::{LX, LanguageSpec},
+    lints::lint,
+    source_map::SourceMap,
+    tokenizer::{TokenizeOptions, tokenize, tokenize_with},
+};
+
+fn lint_source(source: &str, diagnostics: &mut Diagnostics) {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    let chars = analyze_chars(sources.get(file).text());
+    let tokens = tokenize(&chars, file).unwrap();
+    lint(file, sources.get(file), &tokens, &LX, diagnostics);
+}
+
+#[test]
+fn reports_code_after_diverging_if() {
+    let mut diagnostics = Diagnostics::new();
+    lint_source(
+        "loop {\n    if x { break; } else if y { continue; } else { success; }\n    print(x);\n}\nloop { if x { break; } print(x); }\n",
+        &mut diagnostics,
+    );
+    let warnings = diagnostics.diagnostics();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code(), Some(ErrorCode::UnreachableCode));
+    assert_eq!(
+        (warnings[0].line(), warnings[0].column()),
+        (Some(3), Some(5))
+    );
+}
+
+#[test]
+fn levels_control_what_is_reported() {
+    let source = "let s: int = 1;  \n";
+
+    let mut diagnostics = Diagnostics::new();
+    lint_source(source, &mut diagnostics);
+    assert_eq!(diagnostics.count(Severity::Warning), 1);
+
+    let mut diagnostics = Diagnostics::new()
+        .with_level(ErrorCode::KeywordLikeIdentifier, Level::Allow)
+        .with_level(ErrorCode::TrailingWhitespace, Level::Warn);
+    lint_source(source, &mut diagnostics);
+    assert_eq!(
+        diagnostics.diagnostics()[0].code(),
+        Some(ErrorCode::TrailingWhitespace)
+    );
+    assert!(!diagnostics.has_errors());
+
+    let mut diagnostics = Diagnostics::new().with_deny_warnings(true);
+    lint_source(source, &mut diagnostics);
+    assert!(diagnostics.has_errors());
+}
+
+#[test]
+fn keyword_lints_follow_the_language() {
+    let dialect = LanguageSpec::from_toml(
+        "[keywords]\nfn = \"Op\"\nwhen = \"If\"\nlet = \"Let\"\n\n[operators]\n\";\" = \"Semicolon\"\n",
+    )
+    .unwrap();
+    let mut sources = SourceMap::new();
+    let source = "whle; wehn; function; struct;";
+    let file = sources.add("inline.lx", source.to_string());
+    let options = TokenizeOptions::new().with_language(dialect.clone());
+    let tokens = tokenize_with(&analyze_chars(source), file, &options).unwrap();
+    let mut diagnostics = Diagnostics::new();
+    lint(file, sources.get(file), &tokens, &dialect, &mut diagnostics);
+
+    // no `while` or `data` in the dialect, and `op` is spelled `fn`
+    let suggested: Vec<(Option<ErrorCode>, &str)> = diagnostics
+        .diagnostics()
+        .iter()
+        .flat_map(|d| {
+            d.suggestions()
+                .iter()
+                .map(move |s| (d.code(), s.replacement.as_str()))
+        })
+        .collect();
+    assert_eq!(suggested, [(Some(ErrorCode::MisspelledKeyword), "when")]);
+}
@@ -0,0 +1,99 @@
+use lexer::error_handling::{Renderer, to_json};
+use lexer::modules::{
+    analyser::analyze_chars,
+    position::{Position, PositionEncoding},
+    source_map::SourceMap,
+    tokenizer::tokenize,
+};
+
+const SOURCE: &str = "let değişken: string = \"😀 ok\";\nlet ğ = 9x;\n";
+
+#[test]
+fn token_positions_convert_to_every_encoding() {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", SOURCE.to_string());
+    let source = sources.get(file);
+    let tokens = tokenize(&analyze_chars(source.text()), file);
+    // the second line does not lex, so only look at the first one
+    let tokens = tokenize(&analyze_chars(source.line(1).unwrap()), file).or(tokens);
+    let tokens: Vec<_> = tokens
+        .unwrap()
+        .iter()
+        .map(|t| (t.line, t.column, t.length))
+        .collect();
+
+    // `: string = "😀 ok"`: the colon after the identifier, and the string literal
+    let (colon, string) = (tokens[2], tokens[5]);
+    assert_eq!((colon.1, string.1, string.2), (13, 24, 6));
+
+    let start = |column, encoding| source.encode_column(1, column, encoding).unwrap();
+    assert_eq!(start(13, PositionEncoding::Utf32), 13);
+    assert_eq!(start(13, PositionEncoding::Utf16), 13);
+    assert_eq!(start(13, PositionEncoding::Utf8), 15);
+
+    // past the emoji: 1 char, 2 UTF-16 units, 4 bytes
+    assert_eq!(start(26, PositionEncoding::Utf16), 27);
+    assert_eq!(start(26, PositionEncoding::Utf8), 31);
+    for encoding in [
+        PositionEncoding::Utf8,
+        PositionEncoding::Utf16,
+        PositionEncoding::Utf32,
+    ] {
+        let encoded = start(26, encoding);
+        assert_eq!(source.decode_column(1, encoded, encoding), Some(26));
+    }
+    // a column inside the emoji maps back to the emoji
+    assert_eq!(
+        source.decode_column(1, 29, PositionEncoding::Utf8),
+        Some(25)
+    );
+
+    assert_eq!(source.advance(1, 24, 6), Some(Position::new(1, 30)));
+    assert_eq!(source.advance(1, 31, 2), Some(Position::new(2, 2)));
+}
+
+#[test]
+fn diagnostics_report_columns_in_the_requested_encoding() {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", SOURCE.to_string());
+    let err = tokenize(&analyze_chars(sources.get(file).text()), file)
+        .err()
+        .unwrap();
+    assert_eq!((err.line(), err.column()), (Some(2), Some(9)));
+
+    let json = to_json(&err, &sources, PositionEncoding::Utf8);
+    assert_eq!(
+        (json["column"].clone(), json["length"].clone()),
+        (10.into(), 2.into())
+    );
+    assert_eq!(json["position_encoding"], "utf-8");
+
+    let rendered = |encoding| {
+        Renderer::new()
+            .with_color(false)
+            .with_position_encoding(encoding)
+            .render(&err, &sources)
+    };
+    assert!(rendered(PositionEncoding::Utf16).contains(" --> inline.lx:2:9\n"));
+    assert!(rendered(PositionEncoding::Utf8).contains(" --> inline.lx:2:10\n"));
+}
+
+#[test]
+fn carets_line_up_under_wide_and_combined_characters() {
+    let mut sources = SourceMap::new();
+    // "e" + combining acute, then a family emoji made of three people joined by ZWJ
+    let file = sources.add(
+        "inline.lx",
+        "let s: string = \"e\u{301}👨\u{200d}👩\u{200d}👧\" + 1x;\n".to_string(),
+    );
+    let err = tokenize(&analyze_chars(sources.get(file).text()), file)
+        .err()
+        .unwrap();
+    let rendered = err.render(&sources);
+    let lines: Vec<&str> = rendered.lines().collect();
+    let source_line = lines.iter().find(|l| l.starts_with("1 |")).unwrap();
+    let caret_line = lines.iter().find(|l| l.contains('^')).unwrap();
+    // "e\u{301}" is one cell and the family emoji two, so `1x` starts 3 cells after the quote
+    let quote = source_line.find('"').unwrap();
+    assert_eq!(caret_line.find('^').unwrap(), quote + 1 + 3 + 4);
+}
@@ -0,0 +1,162 @@
+use lexer::error_handling::{Error, ErrorCode, ErrorType, Label, Renderer, Severity, Span};
+use lexer::modules::source_map::SourceMap;
+
+fn render(renderer: &Renderer, source: &str, err: impl FnOnce(Error) -> Error) -> String {
+    let mut sources = SourceMap::new();
+    let file = sources.add("main.lx", source.to_string());
+    let err = err(Error::new(ErrorType::LexingError).with_file(file));
+    renderer.render(&err, &sources)
+}
+
+fn plain() -> Renderer {
+    Renderer::new().with_color(false)
+}
+
+#[test]
+fn long_spans_elide_their_middle_lines() {
+    let source = "loop {\n    a;\n    b;\n    c;\n    d;\n    e;\n}\n";
+    let rendered = render(&plain(), source, |err| {
+        err.with_severity(Severity::Warning)
+            .with_code(ErrorCode::UnreachableCode)
+            .with_message("Unreachable code!".to_string())
+            .with_line(2)
+            .with_column(5)
+            .with_label(
+                Label::primary(Span::multiline(2, 5, 6, 7)).with_message("unreachable code"),
+            )
+    });
+    assert_eq!(
+        rendered,
+        concat!(
+            "warning[W0001]: Unreachable code!\n",
+            " --> main.lx:2:5\n",
+            "  |\n",
+            "2 |     a;\n",
+            "  |     ^^\n",
+            "3 |     b;\n",
+            "  |     ^^\n",
+            "...\n",
+            "6 |     e;\n",
+            "  |     ^^ unreachable code\n",
+            "\n",
+            "For more information about this warning, try `enoc --explain W0001`.\n",
+        )
+    );
+}
+
+#[test]
+fn secondary_labels_are_drawn_under_their_own_lines() {
+    let source = "let s = \"abc;\nlet t = 1;\n";
+    let rendered = render(&plain(), source, |err| {
+        err.with_message("Unclosed string literal!".to_string())
+            .with_line(1)
+            .with_column(9)
+            .with_label(Label::primary(Span::new(1, 9, 1)).with_message("string started here"))
+            .with_label(Label::secondary(Span::new(1, 14, 1)).with_message("line ended here"))
+            .with_label(Label::secondary(Span::new(2, 5, 1)).with_message("not part of it"))
+    });
+    assert_eq!(
+        rendered,
+        concat!(
+            "error[LexingError]: Unclosed string literal!\n",
+            " --> main.lx:1:9\n",
+            "  |\n",
+            "1 | let s = \"abc;\n",
+            "  |         ^ string started here\n",
+            "  |              - line ended here\n",
+            "2 | let t = 1;\n",
+            "  |     - not part of it\n",
+        )
+    );
+}
+
+#[test]
+fn the_gutter_widens_for_long_line_numbers() {
+    let source = "x;\n".repeat(98) + "let a = 1;\nlet b = 2;\n";
+    let rendered = render(&plain(), &source, |err| {
+        err.with_message("Two lines!".to_string())
+            .with_line(99)
+            .with_column(5)
+            .with_label(Label::primary(Span::new(99, 5, 1)).with_message("here"))
+            .with_label(Label::secondary(Span::new(100, 5, 1)).with_message("and here"))
+            .with_note("a note")
+            .with_help("some help")
+    });
+    assert_eq!(
+        rendered,
+        concat!(
+            "error[LexingError]: Two lines!\n",
+            "   --> main.lx:99:5\n",
+            "    |\n",
+            " 99 | let a = 1;\n",
+            "    |     ^ here\n",
+            "100 | let b = 2;\n",
+            "    |     - and here\n",
+            "    |\n",
+            "    = note: a note\n",
+            "    = help: some help\n",
+        )
+    );
+}
+
+#[test]
+fn carets_line_up_after_tabs_and_wide_chars() {
+    let source = "\tlet 名前 = x;\n";
+    let rendered = render(&plain().with_tab_width(4), source, |err| {
+        err.with_message("Caret!".to_string())
+            .with_line(1)
+            .with_column(11)
+            .with_label(Label::secondary(Span::new(1, 6, 2)).with_message("a wide name"))
+            .with_label(Label::primary(Span::new(1, 11, 1)).with_message("here"))
+    });
+    assert_eq!(
+        rendered,
+        concat!(
+            "error[LexingError]: Caret!\n",
+            " --> main.lx:1:11\n",
+            "  |\n",
+            "1 |     let 名前 = x;\n",
+            "  |         ---- a wide name\n",
+            "  |                ^ here\n",
+        )
+    );
+}
+
+#[test]
+fn colour_is_only_added_when_asked_for() {
+    let source = "let 1x = 2;\n";
+    let err = |err: Error| {
+        err.with_code(ErrorCode::IdentifierStartsWithDigit)
+            .with_message("Identifier starts with a digit!".to_string())
+            .with_line(1)
+            .with_column(5)
+            .with_length(2)
+    };
+    let plain = render(&plain(), source, err);
+    let colored = render(&Renderer::new().with_color(true), source, err);
+    assert_eq!(
+        plain,
+        concat!(
+            "error[L0004]: Identifier starts with a digit!\n",
+            " --> main.lx:1:5\n",
+            "  |\n",
+            "1 | let 1x = 2;\n",
+            "  |     ^^\n",
+            "\n",
+            "For more information about this error, try `enoc --explain L0004`.\n",
+        )
+    );
+    // the same text, with every part wrapped in its colour
+    assert_eq!(
+        colored,
+        concat!(
+            "\x1b[31;1merror[L0004]\x1b[0m: \x1b[1mIdentifier starts with a digit!\x1b[0m\n",
+            " \x1b[34;1m-->\x1b[0m main.lx:1:5\n",
+            "  \x1b[34;1m|\x1b[0m\n",
+            "\x1b[34;1m1\x1b[0m \x1b[34;1m|\x1b[0m let 1x = 2;\n",
+            "  \x1b[34;1m|\x1b[0m     \x1b[31;1m^^\x1b[0m\n",
+            "\n",
+            "For more information about this error, try `enoc --explain L0004`.\n",
+        )
+    );
+}
@@ -0,0 +1,48 @@
+use std::fs;
+
+use lexer::error_handling::Renderer;
+use lexer::modules::{analyser::analyze_chars, source_map::SourceMap, tokenizer::tokenize};
+
+#[test]
+fn lines_come_from_the_registered_text() {
+    let mut sources = SourceMap::new();
+    let file = sources.add("<stdin>", "let a;\r\nlet b;\n".to_string());
+    let source = sources.get(file);
+    assert_eq!(source.name(), "<stdin>");
+    assert_eq!(source.line_count(), 3);
+    assert_eq!(
+        (source.line(1), source.line(2), source.line(3)),
+        (Some("let a;"), Some("let b;"), Some(""))
+    );
+    assert_eq!((source.line(0), source.line(4)), (None, None));
+}
+
+#[test]
+fn errors_render_from_text_that_is_not_a_file() {
+    let mut sources = SourceMap::new();
+    let file = sources.add("<stdin>", "let s = \"abc;\n".to_string());
+    let err = tokenize(&analyze_chars(sources.get(file).text()), file)
+        .err()
+        .unwrap();
+
+    let rendered = Renderer::new().with_color(false).render(&err, &sources);
+    assert!(rendered.contains(" --> <stdin>:1:9\n"), "{rendered}");
+    assert!(rendered.contains("1 | let s = \"abc;\n"), "{rendered}");
+}
+
+#[test]
+fn errors_render_the_lexed_text_after_the_file_changed() {
+    let path = std::env::temp_dir().join(format!("lexer-source-map-{}.lx", std::process::id()));
+    fs::write(&path, "let s = \"abc;\n").unwrap();
+    let mut sources = SourceMap::new();
+    let file = sources.load(path.to_str().unwrap()).unwrap();
+    fs::write(&path, "changed on disk\n").unwrap();
+
+    let err = tokenize(&analyze_chars(sources.get(file).text()), file)
+        .err()
+        .unwrap();
+    let rendered = Renderer::new().with_color(false).render(&err, &sources);
+    fs::remove_file(&path).unwrap();
+    assert!(rendered.contains("1 | let s = \"abc;\n"), "{rendered}");
+    assert!(!rendered.contains("changed"), "{rendered}");
+}
@@ -0,0 +1,90 @@
+use lexer::error_handling::{ErrorCode, Span};
+use lexer::modules::{
+    analyser::analyze_chars, source_map::SourceMap, tokenizer::tokenize, tokens::Token,
+};
+
+/// Every string literal with its value, position and length.
+fn strings(source: &str) -> Vec<(String, usize, usize, usize)> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    tokenize(&analyze_chars(source), file)
+        .unwrap()
+        .iter()
+        .filter_map(|t| match &t.token {
+            Token::StringLiteral(s) => Some((s.clone(), t.line, t.column, t.length)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn raw_strings_keep_their_text_verbatim() {
+    let found = strings(
+        "let p: string = r\"C:\\path\";\nlet q: string = r#\"say \"hi\"\"#;\nlet t: string = r##\"a \"# b\"##;\n",
+    );
+    assert_eq!(
+        found,
+        [
+            ("C:\\path".to_string(), 1, 17, 10),
+            ("say \"hi\"".to_string(), 2, 17, 13),
+            ("a \"# b".to_string(), 3, 17, 13),
+        ]
+    );
+}
+
+#[test]
+fn multi_line_strings_strip_common_indentation() {
+    let source = "op f() {\n    let s: string = \"\"\"\n        first\n          second\n\n        third\n        \"\"\";\n}\nlet t: string = \"\"\"one line\"\"\";\n";
+    let found = strings(source);
+    assert_eq!(
+        found,
+        [
+            ("first\n  second\n\nthird".to_string(), 2, 21, 61),
+            ("one line".to_string(), 9, 17, 14),
+        ]
+    );
+}
+
+#[test]
+fn ordinary_strings_end_at_the_line_end() {
+    let mut sources = SourceMap::new();
+    let source = "let s: string = \"abc;\nlet t: i32 = 1;\n";
+    let file = sources.add("inline.lx", source.to_string());
+    let err = tokenize(&analyze_chars(source), file).err().unwrap();
+
+    assert_eq!(err.code(), Some(ErrorCode::UnclosedString));
+    assert_eq!((err.line(), err.column()), (Some(1), Some(17)));
+    let end = &err.labels()[1];
+    assert_eq!(end.span, Span::new(1, 22, 1));
+    assert_eq!(end.message.as_deref(), Some("line ended here"));
+}
+
+#[test]
+fn char_literals_without_a_closing_quote_on_the_line_are_unclosed() {
+    let mut sources = SourceMap::new();
+    let source = "let c: char = 'a;\nlet d: char = 'b';\n";
+    let file = sources.add("inline.lx", source.to_string());
+    let err = tokenize(&analyze_chars(source), file).err().unwrap();
+
+    assert_eq!(err.code(), Some(ErrorCode::UnclosedChar));
+    assert_eq!((err.line(), err.column()), (Some(1), Some(15)));
+    let end = &err.labels()[1];
+    assert_eq!(end.span, Span::new(1, 18, 1));
+    assert_eq!(end.message.as_deref(), Some("line ended here"));
+}
+
+#[test]
+fn closed_char_literals_with_several_chars_are_reported_whole() {
+    let mut sources = SourceMap::new();
+    let source = "let c: char = 'abc';\n";
+    let file = sources.add("inline.lx", source.to_string());
+    let err = tokenize(&analyze_chars(source), file).err().unwrap();
+
+    assert_eq!(err.code(), Some(ErrorCode::MultiCharLiteral));
+    assert_eq!((err.line(), err.column()), (Some(1), Some(15)));
+    // the primary label covers `'abc'`, the other one the first extra char
+    assert_eq!(err.labels()[0].span, Span::new(1, 15, 5));
+    let extra = &err.labels()[1];
+    assert_eq!(extra.span, Span::new(1, 17, 1));
+    assert_eq!(extra.message.as_deref(), Some("expected `'` here"));
+}
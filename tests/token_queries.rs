@@ -0,0 +1,134 @@
+use lexer::modules::{
+    analyser::analyze_chars,
+    position::Position,
+    source_map::SourceMap,
+    token_stream::TokenStream,
+    tokenizer::{TokenizeOptions, tokenize_with},
+    tokens::{AnalyzedToken, Token},
+};
+
+fn lex(source: &str) -> TokenStream {
+    let mut sources = SourceMap::new();
+    let file = sources.add("inline.lx", source.to_string());
+    let options = TokenizeOptions::new().with_comments(true);
+    tokenize_with(&analyze_chars(source), file, &options).unwrap()
+}
+
+fn describe(tokens: &TokenStream, token: Option<&AnalyzedToken>) -> String {
+    token.map_or("none".to_string(), |t| {
+        let token = tokens.interner().resolved(&t.token);
+        format!("{:?} {}:{}", token, t.line, t.column)
+    })
+}
+
+const SOURCE: &str = "let café = \"\"\"\n  é\n  \"\"\";\nif x {} else // c\nif y {}\n";
+
+#[test]
+fn tokens_are_found_by_line_and_column() {
+    let tokens = lex(SOURCE);
+    assert_eq!(describe(&tokens, tokens.token_at(1, 1)), "Let 1:1");
+    assert_eq!(describe(&tokens, tokens.token_at(1, 3)), "Let 1:1");
+    assert_eq!(describe(&tokens, tokens.token_at(1, 4)), "none");
+    assert_eq!(
+        describe(&tokens, tokens.token_at(1, 8)),
+        "Identifier(\"café\") 1:5"
+    );
+    // inside a literal spanning three lines
+    assert_eq!(
+        describe(&tokens, tokens.token_at(2, 3)),
+        "StringLiteral(\"é\") 1:12"
+    );
+    assert_eq!(
+        describe(&tokens, tokens.token_at(3, 5)),
+        "StringLiteral(\"é\") 1:12"
+    );
+    assert_eq!(describe(&tokens, tokens.token_at(3, 6)), "Semicolon 3:6");
+    // the comment lies inside `ElseIf`, which covers the `if` on the next line too
+    assert_eq!(describe(&tokens, tokens.token_at(4, 10)), "ElseIf 4:9");
+    assert_eq!(
+        describe(&tokens, tokens.token_at(4, 15)),
+        "Comment(\" c\") 4:14"
+    );
+    assert_eq!(describe(&tokens, tokens.token_at(5, 2)), "ElseIf 4:9");
+    assert_eq!(describe(&tokens, tokens.token_at(5, 3)), "none");
+    assert_eq!(describe(&tokens, tokens.token_at(9, 1)), "none");
+}
+
+#[test]
+fn tokens_are_found_by_byte_offset() {
+    let tokens = lex(SOURCE);
+    let offset = |needle: &str| SOURCE.find(needle).unwrap();
+    assert_eq!(describe(&tokens, tokens.token_at_offset(0)), "Let 1:1");
+    // `é` is two bytes, both belong to the identifier
+    assert_eq!(
+        describe(&tokens, tokens.token_at_offset(offset("é") + 1)),
+        "Identifier(\"café\") 1:5"
+    );
+    assert_eq!(
+        describe(&tokens, tokens.token_at_offset(offset(" ="))),
+        "none"
+    );
+    assert_eq!(
+        describe(&tokens, tokens.token_at_offset(offset("\n  é"))),
+        "StringLiteral(\"é\") 1:12"
+    );
+    assert_eq!(
+        describe(&tokens, tokens.token_at_offset(offset("y"))),
+        "Identifier(\"y\") 5:4"
+    );
+    assert_eq!(
+        describe(&tokens, tokens.token_at_offset(SOURCE.len())),
+        "none"
+    );
+
+    // a stream built by hand has no bytes to search
+    let by_hand = TokenStream::new(Vec::new());
+    assert_eq!(describe(&by_hand, by_hand.token_at_offset(0)), "none");
+}
+
+#[test]
+fn tokens_in_a_range_include_those_reaching_into_it() {
+    let tokens = lex(SOURCE);
+    let kinds = |from: (usize, usize), to: (usize, usize)| -> Vec<String> {
+        tokens
+            .tokens_in_range(Position::new(from.0, from.1)..Position::new(to.0, to.1))
+            .iter()
+            .map(|t| format!("{:?}", tokens.interner().resolved(&t.token)))
+            .collect()
+    };
+    assert_eq!(kinds((2, 1), (3, 7)), ["StringLiteral(\"é\")", "Semicolon"]);
+    assert_eq!(kinds((1, 2), (1, 5)), ["Let"]);
+    assert_eq!(kinds((1, 4), (1, 5)), Vec::<String>::new());
+    assert_eq!(
+        kinds((5, 1), (5, 5)),
+        ["ElseIf", "Comment(\" c\")", "Identifier(\"y\")"]
+    );
+    assert_eq!(kinds((3, 1), (1, 1)), Vec::<String>::new());
+}
+
+#[test]
+fn streams_index_slice_and_iterate_from_both_ends() {
+    let tokens = lex("let x = 1;");
+    assert_eq!(tokens.len(), 5);
+    assert!(!tokens.is_empty());
+    assert!(lex("").is_empty());
+    assert_eq!(tokens[0].token, Token::Let);
+    assert_eq!(tokens[1..3].len(), 2);
+    assert_eq!(tokens[3..][0].token, Token::Number(1));
+
+    let backwards: Vec<&Token> = tokens.iter().rev().map(|t| &t.token).collect();
+    assert_eq!(backwards[0], &Token::Semicolon);
+    assert_eq!(backwards[4], &Token::Let);
+
+    let mut iter = tokens.iter();
+    assert_eq!(iter.next().unwrap().token, Token::Let);
+    assert_eq!(iter.next_back().unwrap().token, Token::Semicolon);
+    assert_eq!(iter.len(), 3);
+
+    let mut count = 0;
+    for token in &tokens {
+        assert!(!token.token.is_comment());
+        count += 1;
+    }
+    assert_eq!(count, 5);
+}